@@ -57,15 +57,27 @@ fn main() {
     let dst = cmake::Config::new("seal")
         .define("SEAL_BUILD_SEAL_C", "ON")
         .define("SEAL_BUILD_STATIC_SEAL_C", "ON")
+        .define("SEAL_USE_ZLIB", cmake_option("CARGO_FEATURE_ZLIB"))
+        .define("SEAL_USE_ZSTD", cmake_option("CARGO_FEATURE_ZSTD"))
         .build();
 
     // link the SEALC lib
     println!("cargo:rustc-link-search=native={}/lib", dst.display());
-    println!("cargo:rustc-link-lib=static=seal-3.7");
-    println!("cargo:rustc-link-lib=static=sealc-3.7");
+    println!("cargo:rustc-link-lib=static=seal-4.1");
+    println!("cargo:rustc-link-lib=static=sealc-4.1");
     println!("cargo:rustc-link-lib=dylib=stdc++");
 }
 
+/// Turn a cargo feature, read from its environment variable, into a CMake
+/// option
+fn cmake_option(feature_var: &str) -> &'static str {
+    if std::env::var_os(feature_var).is_some() {
+        "ON"
+    } else {
+        "OFF"
+    }
+}
+
 fn process_header(header_file: &str) -> String {
     let file = File::open(header_file).unwrap();
     let mut reader = BufReader::new(file);
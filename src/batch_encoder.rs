@@ -1,20 +1,29 @@
 use std::os::raw::*;
 
-use anyhow::Result;
-
-use crate::{context::Context, plain_text::Plaintext, seal_bindings::*};
+use crate::{
+    context::{creation_lock, Context},
+    error::{check_hresult, Result},
+    plain_text::Plaintext,
+    seal_bindings::*,
+};
 
 pub struct BatchEncoder {
     ptr: *mut c_void,
+    // keeps the SEAL context alive
+    _context: Context,
 }
 
 impl BatchEncoder {
     /// Batching is done through an instance of the BatchEncoder class.
     pub fn create(context: &Context) -> Result<BatchEncoder> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
+        let _lock = creation_lock();
         let ret = unsafe { BatchEncoder_Create(context.ptr(), &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the batch encoder");
-        Ok(BatchEncoder { ptr })
+        check_hresult!(ret, "creating the batch encoder");
+        Ok(BatchEncoder {
+            ptr,
+            _context: context.clone(),
+        })
     }
 
     /// The number of slots.
@@ -22,7 +31,7 @@ impl BatchEncoder {
     pub fn slot_count(&self) -> Result<usize> {
         let mut value: u64 = 0;
         let ret = unsafe { BatchEncoder_GetSlotCount(self.ptr, &mut value) };
-        anyhow::ensure!(ret == 0, "Error extracting the slot count");
+        check_hresult!(ret, "extracting the slot count");
         Ok(value as usize)
     }
 
@@ -38,7 +47,7 @@ impl BatchEncoder {
                 plain_text.ptr(),
             )
         };
-        anyhow::ensure!(ret == 0, "Error encoding the batch");
+        check_hresult!(ret, "encoding the batch");
         Ok(plain_text)
     }
 
@@ -50,7 +59,7 @@ impl BatchEncoder {
         // this call creates a new object which is
         // managed through a unique_ptr in the create call
         let ret = unsafe { Plaintext_Pool(plain_text.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the plain text memory pool");
+        check_hresult!(ret, "fetching the plain text memory pool");
         let ret = unsafe {
             BatchEncoder_Decode1(
                 self.ptr,
@@ -60,7 +69,7 @@ impl BatchEncoder {
                 mem_pool_ptr,
             )
         };
-        anyhow::ensure!(ret == 0, "Error decoding the batch");
+        check_hresult!(ret, "decoding the batch");
         Ok(decoded)
     }
 
@@ -72,7 +81,7 @@ impl BatchEncoder {
     // pub fn decrypt(&self, cipher_text: &Ciphertext) -> Result<Plaintext> {
     //     let pt = Plaintext::create_in_pool_of_cipher_text(&cipher_text)?;
     //     let res = unsafe { BatchEncoder_Decrypt(self.ptr, cipher_text.ptr(),
-    // pt.ptr()) };     anyhow::ensure!(ret == 0, "Error decrypting");
+    // pt.ptr()) };     check_hresult!(ret, "decrypting");
     //     Ok(pt)
     // }
 }
@@ -84,3 +93,7 @@ impl Drop for BatchEncoder {
         }
     }
 }
+
+// Encoding and decoding do not modify the SEAL encoder
+unsafe impl Send for BatchEncoder {}
+unsafe impl Sync for BatchEncoder {}
@@ -1,9 +1,16 @@
-use std::os::raw::*;
-
-use anyhow::Result;
+use std::{
+    io::{Read, Write},
+    os::raw::*,
+};
 
 use crate::{
-    context::Context, memory_pool_handle::MemoryPoolHandle, plain_text::Plaintext, seal_bindings::*,
+    context::Context,
+    error::{check_hresult, Result},
+    memory_pool_handle::MemoryPoolHandle,
+    parms_id::ParmsId,
+    plain_text::Plaintext,
+    seal_bindings::*,
+    serialization::{self, ComprMode, LoadFn},
 };
 
 pub struct Ciphertext {
@@ -15,24 +22,25 @@ impl Ciphertext {
     pub fn create() -> Result<Ciphertext> {
         let mut handle_ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { MemoryManager_GetPool2(&mut handle_ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the memory pool handle");
+        check_hresult!(ret, "creating the memory pool handle");
         let mut ptr: *mut c_void = std::ptr::null_mut();
         // this moves the pointer
         let ret = unsafe { Ciphertext_Create1(handle_ptr, &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the cipher text");
+        check_hresult!(ret, "creating the cipher text");
         Ok(Ciphertext { ptr })
     }
 
-    /// Create a `CipherText` in the thread local memory pool
+    /// Create a `CipherText` in the thread local memory pool, with room for
+    /// the first level of the context. The cipher text does not keep a
+    /// reference to the context
     pub fn create_with_context(context: &Context) -> Result<Ciphertext> {
         let mut handle_ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { MemoryManager_GetPool2(&mut handle_ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the memory pool handle");
+        check_hresult!(ret, "creating the memory pool handle");
         let mut ptr: *mut c_void = std::ptr::null_mut();
         // this moves the pointer
-        let ret =
-            unsafe { Ciphertext_Create3(context as *const _ as *mut _, handle_ptr, &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the cipher text");
+        let ret = unsafe { Ciphertext_Create3(context.ptr(), handle_ptr, &mut ptr) };
+        check_hresult!(ret, "creating the cipher text");
         Ok(Ciphertext { ptr })
     }
 
@@ -40,7 +48,7 @@ impl Ciphertext {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         // this moves the pointer
         let ret = unsafe { Ciphertext_Create1(memory_pool_handle.ptr(), &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the cipher text");
+        check_hresult!(ret, "creating the cipher text");
         Ok(Ciphertext { ptr })
     }
 
@@ -49,10 +57,10 @@ impl Ciphertext {
         // this call creates a new object which is
         // managed through a unique_pt in the create call
         let ret = unsafe { Ciphertext_Pool(other.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the cipher text memory pool");
+        check_hresult!(ret, "fetching the cipher text memory pool");
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Ciphertext_Create1(mem_pool_ptr, &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the cipher text");
+        check_hresult!(ret, "creating the cipher text");
         Ok(Ciphertext { ptr })
     }
 
@@ -61,10 +69,10 @@ impl Ciphertext {
         // this call creates a new object which is
         // managed through a unique_pt in the create call
         let ret = unsafe { Plaintext_Pool(other.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the plain text memory pool");
+        check_hresult!(ret, "fetching the plain text memory pool");
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Ciphertext_Create1(mem_pool_ptr, &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the cipher text");
+        check_hresult!(ret, "creating the cipher text");
         Ok(Ciphertext { ptr })
     }
 
@@ -72,30 +80,41 @@ impl Ciphertext {
         self.ptr
     }
 
+    /// Save with the default compression mode of SEAL, see
+    /// `ComprMode::default_mode`
     pub fn save(&self) -> Result<Vec<u8>> {
-        let compression_mode = 1u8; //bzip
-        let mut uncompressed_size: i64 = 0;
-        let ret =
-            unsafe { Ciphertext_SaveSize(self.ptr, compression_mode, &mut uncompressed_size) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error estimating the save size for the cipher text"
-        );
-        let mut actual_size = 0i64;
-        let mut bytes: Vec<u8> = vec![0u8; uncompressed_size as usize];
-        let ret = unsafe {
-            Ciphertext_Save(
-                self.ptr,
-                bytes.as_mut_ptr(),
-                uncompressed_size as u64,
-                compression_mode,
-                &mut actual_size,
-            )
-        };
-        anyhow::ensure!(ret == 0, "Error saving the cipher text");
-        // if compression is 'on', the actual size
-        // will be less than the uncompressed_size
-        Ok(bytes[0..actual_size as usize].to_vec())
+        self.save_with(ComprMode::default_mode()?)
+    }
+
+    /// Save with the given compression mode, which must be supported by
+    /// this build
+    pub fn save_with(&self, mode: ComprMode) -> Result<Vec<u8>> {
+        serialization::save(
+            self.ptr,
+            mode,
+            Ciphertext_SaveSize,
+            Ciphertext_Save,
+            "cipher text",
+        )
+    }
+
+    /// Write the object saved with the default compression mode and return
+    /// the number of bytes written. The C API only saves to memory: the
+    /// object is saved in a buffer of its worst case size, then written.
+    pub fn save_to<W: Write>(&self, writer: W) -> Result<usize> {
+        self.save_to_with(writer, ComprMode::default_mode()?)
+    }
+
+    /// `save_to` with the given compression mode, which must be supported
+    /// by this build
+    pub fn save_to_with<W: Write>(&self, writer: W, mode: ComprMode) -> Result<usize> {
+        serialization::write(writer, &self.save_with(mode)?, "cipher text")
+    }
+
+    /// Read an object written by `save_to`, without reading past it. The C
+    /// API only loads from memory: the object is read in a buffer first.
+    pub fn load_from<R: Read>(context: &Context, reader: R) -> Result<Ciphertext> {
+        Ciphertext::load(context, &mut serialization::read(reader, "cipher text")?)
     }
 
     /// load the cipher text from compressed bytes
@@ -109,66 +128,86 @@ impl Ciphertext {
         context: &Context,
         pool_handle: MemoryPoolHandle,
         bytes: &mut [u8],
+    ) -> Result<Ciphertext> {
+        Ciphertext::load_with(context, pool_handle, bytes, Ciphertext_Load)
+    }
+
+    /// Load the cipher text without checking it against the parameters,
+    /// which is much faster for large cipher texts.
+    ///
+    /// # Safety
+    ///
+    /// SEAL trusts the loaded data: the bytes must come from a trusted
+    /// source, or the cipher text must be checked with `is_valid_for`
+    /// before use, since SEAL reads out of bounds on malformed data.
+    pub unsafe fn unsafe_load(context: &Context, bytes: &mut [u8]) -> Result<Ciphertext> {
+        let pool_handle = MemoryPoolHandle::to_thread_local_pool()?;
+        Ciphertext::load_with(context, pool_handle, bytes, Ciphertext_UnsafeLoad)
+    }
+
+    fn load_with(
+        context: &Context,
+        pool_handle: MemoryPoolHandle,
+        bytes: &mut [u8],
+        load: LoadFn,
     ) -> Result<Ciphertext> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Ciphertext_Create1(pool_handle.ptr(), &mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error instantiating cipher text: {}",
-            std::io::Error::last_os_error()
-        );
+        check_hresult!(ret, "instantiating cipher text");
+        // dropped, and destroyed, if the loading fails
+        let loaded = Ciphertext { ptr };
         let mut _actual_size: i64 = 0;
         let ret = unsafe {
-            Ciphertext_Load(
-                ptr,
+            load(
+                loaded.ptr,
                 context.ptr(),
                 bytes.as_mut_ptr(),
                 bytes.len() as u64,
                 &mut _actual_size,
             )
         };
-        anyhow::ensure!(
-            ret == 0,
-            "Error loading the cipher text: {}",
-            std::io::Error::last_os_error()
-        );
-        Ok(Ciphertext { ptr })
+        check_hresult!(ret, "loading the cipher text");
+        Ok(loaded)
+    }
+
+    /// Whether the cipher text is valid for the parameters of the context,
+    /// e.g. after filling it with `set_raw_rns`
+    pub fn is_valid_for(&self, context: &Context) -> Result<bool> {
+        serialization::is_valid_for(
+            self.ptr,
+            context,
+            ValCheck_Ciphertext_IsValidFor,
+            "cipher text",
+        )
     }
 
     pub fn clone(&self) -> Result<Ciphertext> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Ciphertext_Create2(self.ptr(), &mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error cloning the cipher text: {}",
-            std::io::Error::last_os_error()
-        );
+        check_hresult!(ret, "cloning the cipher text");
         Ok(Ciphertext { ptr })
     }
 
     pub fn size(&self) -> Result<usize> {
         let mut size: u64 = 0;
         let ret = unsafe { Ciphertext_Size(self.ptr(), &mut size) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error getting the cipher text size: {}",
-            std::io::Error::last_os_error()
-        );
+        check_hresult!(ret, "getting the cipher text size");
         Ok(size as usize)
     }
 
     pub fn get_poly_modulus_degree(&self) -> Result<usize> {
         let mut n: u64 = 0;
         let ret = unsafe { Ciphertext_PolyModulusDegree(self.ptr(), &mut n) };
-        anyhow::ensure!(ret == 0, "Error getting the polymodulus degree",);
+        check_hresult!(ret, "getting the polymodulus degree");
         Ok(n as usize)
     }
 
     pub fn get_coeff_modulus_length(&self) -> Result<usize> {
         let mut k: u64 = 0;
         let ret = unsafe { Ciphertext_CoeffModulusSize(self.ptr(), &mut k) };
-        anyhow::ensure!(ret == 0, "Error getting the coeff modulus size",);
-        // TODO: why is it not equal to parms.get_coeff_modulus().size() ?
+        check_hresult!(ret, "getting the coeff modulus size");
+        // the special prime of the key level is never part of a cipher text,
+        // see `Context::get_coeff_modulus_count` and `ContextData`
         Ok(k as usize)
     }
 
@@ -179,21 +218,20 @@ impl Ciphertext {
     pub fn scale(&self) -> Result<f64> {
         let mut scale: f64 = 0.0;
         let ret = unsafe { Ciphertext_Scale(self.ptr(), &mut scale) };
-        anyhow::ensure!(ret == 0, "Error getting the scale");
+        check_hresult!(ret, "getting the scale");
         Ok(scale)
     }
 
-    pub fn set_scale(&self, scale: &f64) -> Result<()> {
+    pub fn set_scale(&mut self, scale: &f64) -> Result<()> {
         let ret = unsafe { Ciphertext_SetScale(self.ptr(), *scale) };
-        anyhow::ensure!(ret == 0, "Error setting the scale {}", scale);
+        check_hresult!(ret, "setting the scale {}", scale);
         Ok(())
     }
 
-    pub fn parms_id(&self) -> Result<Vec<u64>> {
-        let mut parms_id = vec![0u64; 4];
-        let ret = unsafe { Ciphertext_ParmsId(self.ptr(), parms_id.as_mut_ptr()) };
-        anyhow::ensure!(ret == 0, "Error getting the parms id");
-        Ok(parms_id)
+    /// The id of the level of the modulus switching chain the cipher text
+    /// is at
+    pub fn parms_id(&self) -> Result<ParmsId> {
+        ParmsId::read(self.ptr(), Ciphertext_ParmsId, "cipher text")
     }
 
     /// Get the raw RNS data structure. It consists of a vector of `u64` of
@@ -208,12 +246,7 @@ impl Ciphertext {
             .map(|index| -> Result<u64> {
                 let mut coeff = 0;
                 let ret = unsafe { Ciphertext_GetDataAt1(self.ptr(), index as u64, &mut coeff) };
-                anyhow::ensure!(
-                    ret == 0,
-                    "Could not get coefficient {} from the given ciphertext ({})!",
-                    index,
-                    ret
-                );
+                check_hresult!(ret, "getting the coefficient {} of the cipher text", index);
                 Ok(coeff)
             })
             .collect()
@@ -226,20 +259,19 @@ impl Ciphertext {
     /// polynomials have `poly_modulus_degree` coefficients. And each
     /// coefficient is stored in its RNS representation which is a vector of
     /// length the number of primes in the factorisation of the `coeff_modulus`
-    pub fn set_raw_rns(&self, polynomials: Vec<u64>) -> Result<()> {
+    pub fn set_raw_rns(&mut self, polynomials: Vec<u64>) -> Result<()> {
         for (index, coeff) in polynomials.iter().enumerate() {
             let ret = unsafe { Ciphertext_SetDataAt(self.ptr(), index as u64, *coeff) };
-            anyhow::ensure!(
-                ret == 0,
-                "Could not set polynomial on index {} of the given ciphertext: {}",
-                index,
-                std::io::Error::last_os_error()
+            check_hresult!(
+                ret,
+                "setting the polynomial at index {} of the cipher text",
+                index
             );
         }
         Ok(())
     }
 
-    pub fn try_add_assign(&self, other: &Self, modulus: &[u64]) -> Result<()> {
+    pub fn try_add_assign(&mut self, other: &Self, modulus: &[u64]) -> Result<()> {
         let (mut a, b) = (self.get_raw_rns()?, other.get_raw_rns()?);
         let mut index = 0;
         for _ in 0..self.size()? {
@@ -261,3 +293,7 @@ impl Drop for Ciphertext {
         }
     }
 }
+
+// Cipher texts are only modified through `&mut self`
+unsafe impl Send for Ciphertext {}
+unsafe impl Sync for Ciphertext {}
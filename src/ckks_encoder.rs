@@ -1,23 +1,31 @@
 use std::os::raw::*;
 
-use anyhow::Result;
-
-use crate::{context::Context, plain_text::Plaintext, seal_bindings::*};
+use crate::{
+    context::{creation_lock, Context},
+    error::{check_hresult, Result},
+    parms_id::ParmsId,
+    plain_text::Plaintext,
+    seal_bindings::*,
+};
 
 pub struct CKKSEncoder {
     ptr: *mut c_void,
-    parms_id: Vec<u64>,
+    parms_id: ParmsId,
+    // keeps the SEAL context alive
+    _context: Context,
 }
 
 impl CKKSEncoder {
     /// Batching is done through an instance of the CKKSEncoder class.
     pub fn create(context: &Context) -> Result<CKKSEncoder> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
+        let _lock = creation_lock();
         let ret = unsafe { CKKSEncoder_Create(context.ptr(), &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the CKKS encoder");
+        check_hresult!(ret, "creating the CKKS encoder");
         Ok(CKKSEncoder {
             ptr,
             parms_id: context.first_parms_id()?,
+            _context: context.clone(),
         })
     }
 
@@ -26,7 +34,7 @@ impl CKKSEncoder {
     pub fn slot_count(&self) -> Result<usize> {
         let mut value: u64 = 0;
         let ret = unsafe { CKKSEncoder_SlotCount(self.ptr, &mut value) };
-        anyhow::ensure!(ret == 0, "Error extracting the slot count");
+        check_hresult!(ret, "extracting the slot count");
         Ok(value as usize)
     }
 
@@ -37,7 +45,7 @@ impl CKKSEncoder {
         // this call creates a new object which is
         // managed through a unique_ptr in the create call
         let ret = unsafe { Plaintext_Pool(plain_text.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the plain text memory pool");
+        check_hresult!(ret, "fetching the plain text memory pool");
         let ret = unsafe {
             CKKSEncoder_Encode1(
                 self.ptr,
@@ -49,7 +57,7 @@ impl CKKSEncoder {
                 mem_pool_ptr,
             )
         };
-        anyhow::ensure!(ret == 0, "Error encoding values with the CKKS encoder");
+        check_hresult!(ret, "encoding values with the CKKS encoder");
         Ok(plain_text)
     }
 
@@ -60,7 +68,7 @@ impl CKKSEncoder {
         // this call creates a new object which is
         // managed through a unique_pt in the create call
         let ret = unsafe { Plaintext_Pool(plain_text.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the plain text memory pool");
+        check_hresult!(ret, "fetching the plain text memory pool");
         let ret = unsafe {
             CKKSEncoder_Encode3(
                 self.ptr,
@@ -71,7 +79,7 @@ impl CKKSEncoder {
                 mem_pool_ptr,
             )
         };
-        anyhow::ensure!(ret == 0, "Error encoding value with the CKKS encoder");
+        check_hresult!(ret, "encoding value with the CKKS encoder");
         Ok(plain_text)
     }
 
@@ -83,7 +91,7 @@ impl CKKSEncoder {
         // this call creates a new object which is
         // managed through a unique_pt in the create call
         let ret = unsafe { Plaintext_Pool(plain_text.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the plain text memory pool");
+        check_hresult!(ret, "fetching the plain text memory pool");
         let ret = unsafe {
             CKKSEncoder_Decode1(
                 self.ptr,
@@ -93,7 +101,7 @@ impl CKKSEncoder {
                 mem_pool_ptr,
             )
         };
-        anyhow::ensure!(ret == 0, "Error decoding the CKKS encoder");
+        check_hresult!(ret, "decoding the CKKS encoder");
         Ok(decoded)
     }
 }
@@ -105,3 +113,7 @@ impl Drop for CKKSEncoder {
         }
     }
 }
+
+// Encoding and decoding do not modify the SEAL encoder
+unsafe impl Send for CKKSEncoder {}
+unsafe impl Sync for CKKSEncoder {}
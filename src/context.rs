@@ -1,113 +1,309 @@
-use crate::{params::Params, seal_bindings::*};
-use anyhow::Result;
-use std::os::raw::*;
+use crate::{
+    context_data::ContextData,
+    error::{check_hresult, Result, SealError},
+    params::{Params, SecurityLevel},
+    parms_id::ParmsId,
+    seal_bindings::*,
+};
+use std::{
+    os::raw::*,
+    sync::{Arc, Mutex, MutexGuard},
+};
 
-pub struct Context {
+/// SEAL is thread safe for operations but not for the creation of some
+/// objects (contexts, encoders, ...): their constructors take this lock
+static CREATION_LOCK: Mutex<()> = Mutex::new(());
+
+pub(crate) fn creation_lock() -> MutexGuard<'static, ()> {
+    // the lock protects no data: a panic while holding it is harmless
+    CREATION_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The SEAL context, destroyed when the last `Context` sharing it is dropped
+struct ContextHandle {
     ptr: *mut ::std::os::raw::c_void,
-    // these parameters can be recovered from the C++ API level
-    // and hence duplicate them... but we do not carry usually more
-    // than one context
-    params: Params,
-    security_level: u8,
+    security_level: SecurityLevel,
+    expand_mod_chain: bool,
+}
+
+// The SEAL context is immutable once created
+unsafe impl Send for ContextHandle {}
+unsafe impl Sync for ContextHandle {}
+
+impl Drop for ContextHandle {
+    fn drop(&mut self) {
+        unsafe {
+            SEALContext_Destroy(self.ptr);
+        }
+    }
+}
+
+/// A shared handle to a SEAL context.
+///
+/// Cloning a `Context` is cheap: the clones share the same SEAL context.
+/// The encoders, the encryptor, the decryptor, the evaluator and the key
+/// generator keep a clone of the context they were created with, so the
+/// SEAL context is only destroyed once all of them are dropped.
+///
+/// A `Context` can be shared between threads.
+#[derive(Clone)]
+pub struct Context {
+    handle: Arc<ContextHandle>,
 }
 
 impl Context {
-    pub fn create(params: Params, security_level: u8, expand_mod_chain: bool) -> Result<Context> {
+    pub fn create(
+        params: Params,
+        security_level: SecurityLevel,
+        expand_mod_chain: bool,
+    ) -> Result<Context> {
         let mut ptr = std::ptr::null_mut();
+        let _lock = creation_lock();
         unsafe {
             // `ret` is always 0 here
             SEALContext_Create(
                 params.ptr(),
                 if expand_mod_chain { 1 } else { 0 },
-                security_level as i32,
+                security_level.into(),
                 &mut ptr,
             );
         };
 
         let context = Context {
-            ptr,
-            params,
-            security_level,
+            handle: Arc::new(ContextHandle {
+                ptr,
+                security_level,
+                expand_mod_chain,
+            }),
         };
 
-        // Check if there is a parameter error
-        let (err, msg) = (context.get_error_name(), context.get_error_msg());
-        if err != "success" || msg != "valid" {
-            anyhow::bail!("Error {}: {}", err, msg);
+        context.check_parameters()?;
+        Ok(context)
+    }
+
+    /// Check the qualifiers SEAL computed for the parameters and report the
+    /// validation error, if any
+    fn check_parameters(&self) -> Result<()> {
+        let data = self.key_context_data()?;
+        let mut epq_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { ContextData_Qualifiers(data.ptr(), &mut epq_ptr) };
+        check_hresult!(ret, "getting the parameters qualifiers");
+        let mut parameters_set = 0;
+        let ret = unsafe { EPQ_ParametersSet(epq_ptr, &mut parameters_set) };
+        let result = if ret != 0 {
+            Err(SealError::from_hresult(
+                ret,
+                "checking the parameters qualifiers".to_string(),
+            ))
+        } else if parameters_set == 0 {
+            match (
+                read_string(epq_ptr, EPQ_ParameterErrorName),
+                read_string(epq_ptr, EPQ_ParameterErrorMessage),
+            ) {
+                (Ok(name), Ok(message)) => Err(SealError::InvalidParameters { name, message }),
+                (Err(err), _) | (_, Err(err)) => Err(err),
+            }
         } else {
-            Ok(context)
-        }
+            Ok(())
+        };
+        unsafe { EPQ_Destroy(epq_ptr) };
+        result
     }
 
-    pub fn get_error_name(&self) -> String {
-        let mut length: u64 = 0;
-        let mut buf = vec![0_u8; 512];
-        unsafe {
-            SEALContext_ParameterErrorName(self.ptr, buf.as_mut_ptr() as *mut i8, &mut length);
+    /// The name of the error found by SEAL when validating the parameters
+    /// of the context, `success` when they are valid
+    pub fn get_error_name(&self) -> Result<String> {
+        read_string(self.ptr(), SEALContext_ParameterErrorName)
+    }
+
+    /// The description of the error found by SEAL when validating the
+    /// parameters of the context
+    pub fn get_error_msg(&self) -> Result<String> {
+        read_string(self.ptr(), SEALContext_ParameterErrorMessage)
+    }
+
+    pub(crate) fn ptr(&self) -> *mut c_void {
+        self.handle.ptr
+    }
+
+    pub fn security_level(&self) -> SecurityLevel {
+        self.handle.security_level
+    }
+
+    /// Whether the context was created with the full modulus switching chain
+    pub fn expand_mod_chain(&self) -> bool {
+        self.handle.expand_mod_chain
+    }
+
+    /// A copy of the encryption parameters the context was created with
+    pub fn parameters(&self) -> Result<Params> {
+        self.key_context_data()?.parms()
+    }
+
+    /// The level of the modulus switching chain holding all the primes,
+    /// used for the keys
+    pub fn key_context_data(&self) -> Result<ContextData<'_>> {
+        self.context_data_with(SEALContext_KeyContextData, "key")
+    }
+
+    /// The level data is encrypted at
+    pub fn first_context_data(&self) -> Result<ContextData<'_>> {
+        self.context_data_with(SEALContext_FirstContextData, "first")
+    }
+
+    /// The level with a single prime left, at chain index 0
+    pub fn last_context_data(&self) -> Result<ContextData<'_>> {
+        self.context_data_with(SEALContext_LastContextData, "last")
+    }
+
+    fn context_data_with(
+        &self,
+        f: unsafe extern "C" fn(*mut c_void, *mut *mut c_void) -> c_long,
+        name: &str,
+    ) -> Result<ContextData<'_>> {
+        let mut data_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { f(self.ptr(), &mut data_ptr) };
+        check_hresult!(ret, "getting the {} context data", name);
+        ContextData::from_ptr(data_ptr).ok_or_else(|| {
+            SealError::invalid(
+                format!("getting the {} context data", name),
+                "the context has no such context data",
+            )
+        })
+    }
+
+    /// The level of the chain with the given parms id, if any
+    pub fn context_data(&self, parms_id: &ParmsId) -> Result<Option<ContextData<'_>>> {
+        let mut parms_id = *parms_id;
+        let mut data_ptr: *mut c_void = std::ptr::null_mut();
+        let ret =
+            unsafe { SEALContext_GetContextData(self.ptr(), parms_id.as_mut_ptr(), &mut data_ptr) };
+        check_hresult!(ret, "getting the context data for the parms id");
+        Ok(ContextData::from_ptr(data_ptr))
+    }
+
+    /// The level of the chain with the given chain index: the key level has
+    /// the highest index and the last level the index 0
+    pub fn context_data_at(&self, chain_index: usize) -> Result<ContextData<'_>> {
+        let mut context_data = self.key_context_data()?;
+        let key_index = context_data.chain_index()?;
+        if chain_index > key_index {
+            return Err(SealError::invalid(
+                format!("getting the context data at chain index {}", chain_index),
+                format!("the highest chain index is {}", key_index),
+            ));
         }
-        buf.resize(length as usize, 0_u8);
-        String::from_utf8_lossy(&buf).to_string()
+        for _ in chain_index..key_index {
+            context_data = context_data.next()?.ok_or_else(|| {
+                SealError::invalid(
+                    format!("getting the context data at chain index {}", chain_index),
+                    "the modulus switching chain is broken",
+                )
+            })?;
+        }
+        Ok(context_data)
     }
 
-    pub fn get_error_msg(&self) -> String {
-        let mut length: u64 = 0;
-        let mut buf = vec![0_u8; 512];
-        unsafe {
-            SEALContext_ParameterErrorMessage(self.ptr, buf.as_mut_ptr() as *mut i8, &mut length);
+    /// All the levels of the modulus switching chain, from the key level to
+    /// the last level
+    pub fn chain(&self) -> Result<Vec<ContextData<'_>>> {
+        let mut chain = vec![self.key_context_data()?];
+        while let Some(next) = chain[chain.len() - 1].next()? {
+            chain.push(next);
         }
-        buf.resize(length as usize, 0_u8);
-        String::from_utf8_lossy(&buf).to_string()
+        Ok(chain)
     }
 
-    pub(crate) fn ptr(&self) -> *mut c_void {
-        self.ptr
+    /// The parms id of the level with the given chain index, to be used as
+    /// the target of `Evaluator::mod_switch_to`
+    pub fn parms_id_at(&self, chain_index: usize) -> Result<ParmsId> {
+        self.context_data_at(chain_index)?.parms_id()
+    }
+
+    /// The id of the key level
+    pub fn key_parms_id(&self) -> Result<ParmsId> {
+        ParmsId::read(self.ptr(), SEALContext_KeyParmsId, "key level")
     }
 
-    pub fn security_level(&self) -> u8 {
-        self.security_level
+    /// The id of the level fresh cipher texts are at
+    pub fn first_parms_id(&self) -> Result<ParmsId> {
+        ParmsId::read(self.ptr(), SEALContext_FirstParmsId, "first level")
     }
 
-    pub fn parameters(&self) -> &Params {
-        &self.params
+    /// The id of the last level
+    pub fn last_parms_id(&self) -> Result<ParmsId> {
+        ParmsId::read(self.ptr(), SEALContext_LastParmsId, "last level")
     }
 
-    pub fn first_parms_id(&self) -> Result<Vec<u64>> {
-        let mut parms_id = vec![0u64; 4];
-        let ret = unsafe { SEALContext_FirstParmsId(self.ptr, parms_id.as_mut_ptr()) };
-        anyhow::ensure!(ret == 0, "unable to get the first parms id: ");
-        Ok(parms_id)
+    /// The Galois element of a rotation by `step` slots, computed as SEAL
+    /// does: positive steps rotate to the left, negative ones to the right
+    /// and 0 swaps the rows of the batching matrix (the complex conjugation
+    /// for CKKS)
+    pub fn galois_elt_from_step(&self, step: i32) -> Result<u32> {
+        let degree = self
+            .key_context_data()?
+            .parms()?
+            .get_poly_modulus_degree()? as u64;
+        let m = 2 * degree;
+        if step == 0 {
+            return Ok((m - 1) as u32);
+        }
+        let pos_step = step.unsigned_abs() as u64;
+        if pos_step >= degree / 2 {
+            return Err(SealError::invalid(
+                "computing the galois element",
+                format!("the step count {} is too large for degree {}", step, degree),
+            ));
+        }
+        // a rotation to the right is a rotation to the left of the
+        // remaining slots of the row
+        let left_steps = if step < 0 {
+            degree / 2 - pos_step
+        } else {
+            pos_step
+        };
+        // the generator of the rotations is 3
+        let mut galois_elt = 1u64;
+        for _ in 0..left_steps {
+            galois_elt = (galois_elt * 3) & (m - 1);
+        }
+        Ok(galois_elt as u32)
     }
 
+    /// The number of primes at the first level, which is the
+    /// `Ciphertext::get_coeff_modulus_length` of a fresh cipher text: the
+    /// special prime of the key level is not included
     pub fn get_coeff_modulus_count(&self) -> Result<u64> {
         let mut count: u64 = 0;
-        let mut data_ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { SEALContext_FirstContextData(self.ptr, &mut data_ptr) };
-        anyhow::ensure!(ret == 0, "unable to get the context: ");
+        let data = self.first_context_data()?;
         let ret =
-            unsafe { ContextData_TotalCoeffModulus(data_ptr, &mut count, std::ptr::null_mut()) };
-        anyhow::ensure!(ret == 0, "unable to get the number of Coeff Modulus");
+            unsafe { ContextData_TotalCoeffModulus(data.ptr(), &mut count, std::ptr::null_mut()) };
+        check_hresult!(ret, "getting the number of Coeff Modulus");
         Ok(count)
     }
 
+    /// All the primes of the coefficient modulus, including the special
+    /// prime. Use `ContextData::coeff_modulus` for the primes of a level
     pub fn get_coeff_modulus(&self) -> Result<Vec<u64>> {
-        let mut coeffs_len: u64 = self.get_coeff_modulus_count()? + 1;
-        let mut primes = vec![std::ptr::null_mut(); coeffs_len as usize];
-        let ret = unsafe {
-            EncParams_GetCoeffModulus(self.params.ptr(), &mut coeffs_len, primes.as_mut_ptr())
-        };
-        anyhow::ensure!(ret == 0, "unable to get the Coeff Modulus: ");
-        let mut coeffs = vec![0; coeffs_len as usize];
-        for i in 0..coeffs_len as usize {
-            unsafe { Modulus_Value(primes[i], &mut coeffs[i]) };
-        }
-        Ok(coeffs)
+        self.key_context_data()?.coeff_modulus()
     }
 }
 
-impl Drop for Context {
-    fn drop(&mut self) {
-        unsafe {
-            SEALContext_Destroy(self.ptr);
-        }
-    }
+/// Read a string from the C API: a first call returns the length, the second
+/// one fills the buffer
+fn read_string(
+    ptr: *mut c_void,
+    f: unsafe extern "C" fn(*mut c_void, *mut c_char, *mut u64) -> c_long,
+) -> Result<String> {
+    let mut length: u64 = 0;
+    let ret = unsafe { f(ptr, std::ptr::null_mut(), &mut length) };
+    check_hresult!(ret, "getting the string length");
+    // the C API writes the trailing null character
+    let mut buf = vec![0_u8; length as usize + 1];
+    let ret = unsafe { f(ptr, buf.as_mut_ptr() as *mut c_char, &mut length) };
+    check_hresult!(ret, "reading the string");
+    buf.truncate(length as usize);
+    Ok(String::from_utf8_lossy(&buf).to_string())
 }
@@ -0,0 +1,143 @@
+use std::{marker::PhantomData, os::raw::*};
+
+use crate::{
+    context::Context,
+    error::{check_hresult, Result},
+    params::{Params, SecurityLevel},
+    parms_id::ParmsId,
+    seal_bindings::*,
+};
+
+/// The properties SEAL computed for the encryption parameters of a level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Qualifiers {
+    pub parameters_set: bool,
+    pub using_fft: bool,
+    pub using_ntt: bool,
+    pub using_batching: bool,
+    pub using_fast_plain_lift: bool,
+    pub using_descending_modulus_chain: bool,
+    pub security_level: SecurityLevel,
+}
+
+/// The pre-computation data of one level of the modulus switching chain.
+///
+/// The chain starts with the key level, which holds all the primes,
+/// including the special prime, and has the highest chain index. Data is
+/// encrypted at the first level (the next one) and each modulus switch
+/// drops a prime until the last level, of chain index 0.
+///
+/// The data is owned by the `Context` and cannot outlive it.
+pub struct ContextData<'a> {
+    ptr: *mut c_void,
+    context: PhantomData<&'a Context>,
+}
+
+impl<'a> ContextData<'a> {
+    /// Wrap a pointer to a context data owned by the context, or return
+    /// `None` if the pointer is null
+    pub(crate) fn from_ptr(ptr: *mut c_void) -> Option<ContextData<'a>> {
+        if ptr.is_null() {
+            None
+        } else {
+            Some(ContextData {
+                ptr,
+                context: PhantomData,
+            })
+        }
+    }
+
+    pub(crate) fn ptr(&self) -> *mut c_void {
+        self.ptr
+    }
+
+    /// The index of this level in the chain: 0 for the last level
+    pub fn chain_index(&self) -> Result<usize> {
+        let mut index: u64 = 0;
+        let ret = unsafe { ContextData_ChainIndex(self.ptr, &mut index) };
+        check_hresult!(ret, "getting the chain index");
+        Ok(index as usize)
+    }
+
+    /// A copy of the encryption parameters of this level
+    pub fn parms(&self) -> Result<Params> {
+        let mut ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { ContextData_Parms(self.ptr, &mut ptr) };
+        check_hresult!(ret, "getting the context data parameters");
+        Ok(Params::from_ptr(ptr))
+    }
+
+    /// The parms id of this level, the target of `Evaluator::mod_switch_to`
+    pub fn parms_id(&self) -> Result<ParmsId> {
+        self.parms()?.parms_id()
+    }
+
+    /// The primes of the coefficient modulus at this level
+    pub fn coeff_modulus(&self) -> Result<Vec<u64>> {
+        self.parms()?.get_coeff_modulus()
+    }
+
+    /// The bit count of the product of the primes of this level
+    pub fn total_coeff_modulus_bit_count(&self) -> Result<u32> {
+        let mut bit_count = 0;
+        let ret = unsafe { ContextData_TotalCoeffModulusBitCount(self.ptr, &mut bit_count) };
+        check_hresult!(ret, "getting the total coeff modulus bit count");
+        Ok(bit_count as u32)
+    }
+
+    /// The qualifiers of the parameters of this level
+    pub fn qualifiers(&self) -> Result<Qualifiers> {
+        let mut epq_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { ContextData_Qualifiers(self.ptr, &mut epq_ptr) };
+        check_hresult!(ret, "getting the parameters qualifiers");
+        let qualifiers = read_qualifiers(epq_ptr);
+        unsafe { EPQ_Destroy(epq_ptr) };
+        qualifiers
+    }
+
+    /// The next level in the chain, with one prime less, if any
+    pub fn next(&self) -> Result<Option<ContextData<'a>>> {
+        let mut ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { ContextData_NextContextData(self.ptr, &mut ptr) };
+        check_hresult!(ret, "getting the next context data");
+        Ok(ContextData::from_ptr(ptr))
+    }
+
+    /// The previous level in the chain, with one prime more, if any
+    pub fn prev(&self) -> Result<Option<ContextData<'a>>> {
+        let mut ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { ContextData_PrevContextData(self.ptr, &mut ptr) };
+        check_hresult!(ret, "getting the previous context data");
+        Ok(ContextData::from_ptr(ptr))
+    }
+}
+
+fn read_flag(
+    epq_ptr: *mut c_void,
+    f: unsafe extern "C" fn(*mut c_void, *mut c_int) -> c_long,
+    name: &str,
+) -> Result<bool> {
+    let mut flag = 0;
+    let ret = unsafe { f(epq_ptr, &mut flag) };
+    check_hresult!(ret, "reading the qualifier: {}", name);
+    Ok(flag != 0)
+}
+
+fn read_qualifiers(epq_ptr: *mut c_void) -> Result<Qualifiers> {
+    let mut security_level = 0;
+    let ret = unsafe { EPQ_SecLevel(epq_ptr, &mut security_level) };
+    check_hresult!(ret, "reading the qualifier: security level");
+    Ok(Qualifiers {
+        parameters_set: read_flag(epq_ptr, EPQ_ParametersSet, "parameters set")?,
+        using_fft: read_flag(epq_ptr, EPQ_UsingFFT, "using FFT")?,
+        using_ntt: read_flag(epq_ptr, EPQ_UsingNTT, "using NTT")?,
+        using_batching: read_flag(epq_ptr, EPQ_UsingBatching, "using batching")?,
+        using_fast_plain_lift: read_flag(epq_ptr, EPQ_UsingFastPlainLift, "fast plain lift")?,
+        using_descending_modulus_chain: read_flag(
+            epq_ptr,
+            EPQ_UsingDescendingModulusChain,
+            "descending modulus chain",
+        )?,
+        security_level: SecurityLevel::try_from(security_level)?,
+    })
+}
@@ -1,14 +1,18 @@
 use std::os::raw::*;
 
-use anyhow::Result;
-
 use crate::{
-    cipher_text::Ciphertext, context::Context, key_generator::SecretKey, plain_text::Plaintext,
+    cipher_text::Ciphertext,
+    context::Context,
+    error::{check_hresult, Result},
+    key_generator::SecretKey,
+    plain_text::Plaintext,
     seal_bindings::*,
 };
 
 pub struct Decryptor {
     ptr: *mut ::std::os::raw::c_void,
+    // keeps the SEAL context alive
+    _context: Context,
 }
 
 impl Decryptor {
@@ -22,8 +26,11 @@ impl Decryptor {
     pub fn create(context: &Context, secret_key: &SecretKey) -> Result<Decryptor> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Decryptor_Create(context.ptr(), secret_key.ptr(), &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the decryptor");
-        Ok(Decryptor { ptr })
+        check_hresult!(ret, "creating the decryptor");
+        Ok(Decryptor {
+            ptr,
+            _context: context.clone(),
+        })
     }
 
     #[allow(dead_code)]
@@ -44,7 +51,7 @@ impl Decryptor {
     pub fn decrypt(&self, cipher_text: &Ciphertext) -> Result<Plaintext> {
         let pt = Plaintext::create_in_pool_of_cipher_text(cipher_text)?;
         let ret = unsafe { Decryptor_Decrypt(self.ptr, cipher_text.ptr(), pt.ptr()) };
-        anyhow::ensure!(ret == 0, "Error decrypting ({})", ret);
+        check_hresult!(ret, "decrypting");
         Ok(pt)
     }
 
@@ -76,7 +83,7 @@ impl Decryptor {
         let ret = unsafe {
             Decryptor_InvariantNoiseBudget(self.ptr, cipher_text.ptr(), &mut noise_budget)
         };
-        anyhow::ensure!(ret == 0, "Error getting the noise budget");
+        check_hresult!(ret, "getting the noise budget");
         Ok(noise_budget)
     }
 }
@@ -88,3 +95,7 @@ impl Drop for Decryptor {
         }
     }
 }
+
+// SEAL decryptors can be used concurrently
+unsafe impl Send for Decryptor {}
+unsafe impl Sync for Decryptor {}
@@ -1,31 +1,80 @@
-use std::os::raw::*;
-
-use anyhow::Result;
+use std::{os::raw::*, sync::OnceLock};
 
 use crate::{
     cipher_text::Ciphertext,
     context::Context,
+    error::{check_hresult, Result},
+    evaluator::Evaluator,
     key_generator::{PublicKey, SecretKey},
+    parms_id::ParmsId,
     plain_text::Plaintext,
     seal_bindings::*,
+    serialization::Serializable,
 };
 
 pub struct Encryptor {
     ptr: *mut ::std::os::raw::c_void,
+    // keeps the SEAL context alive
+    context: Context,
+    // whether `encrypt_zero` encrypts with the public key or the secret key
+    has_public_key: bool,
+    // adds the encryptions of zero of `rerandomize`, created on first use
+    evaluator: OnceLock<Evaluator>,
 }
 
+/// Encrypts with the public key, `encrypt`, or with the secret key,
+/// `encrypt_symmetric`. The secret key encryption produces less noise and
+/// its output can be seeded, see `encrypt_symmetric_serializable`.
 impl Encryptor {
+    /// An encryptor holding both keys
     pub fn create(
         context: &Context,
         public_key: &PublicKey,
         secret_key: &SecretKey,
+    ) -> Result<Encryptor> {
+        Encryptor::create_with_keys(context, public_key.ptr(), secret_key.ptr())
+    }
+
+    /// An encryptor for the senders which only know the public key
+    pub fn with_public_key(context: &Context, public_key: &PublicKey) -> Result<Encryptor> {
+        Encryptor::create_with_keys(context, public_key.ptr(), std::ptr::null_mut())
+    }
+
+    /// An encryptor for the owner of the secret key, which only encrypts
+    /// symmetrically
+    pub fn with_secret_key(context: &Context, secret_key: &SecretKey) -> Result<Encryptor> {
+        Encryptor::create_with_keys(context, std::ptr::null_mut(), secret_key.ptr())
+    }
+
+    fn create_with_keys(
+        context: &Context,
+        public_key: *mut c_void,
+        secret_key: *mut c_void,
     ) -> Result<Encryptor> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe {
-            Encryptor_Create(context.ptr(), public_key.ptr(), secret_key.ptr(), &mut ptr)
-        };
-        anyhow::ensure!(ret == 0, "Error creating the encryptor");
-        Ok(Encryptor { ptr })
+        let ret = unsafe { Encryptor_Create(context.ptr(), public_key, secret_key, &mut ptr) };
+        check_hresult!(ret, "creating the encryptor");
+        Ok(Encryptor {
+            ptr,
+            context: context.clone(),
+            has_public_key: !public_key.is_null(),
+            evaluator: OnceLock::new(),
+        })
+    }
+
+    /// Replace or add the public key used by `encrypt`
+    pub fn set_public_key(&mut self, public_key: &PublicKey) -> Result<()> {
+        let ret = unsafe { Encryptor_SetPublicKey(self.ptr, public_key.ptr()) };
+        check_hresult!(ret, "setting the public key of the encryptor");
+        self.has_public_key = true;
+        Ok(())
+    }
+
+    /// Replace or add the secret key used by `encrypt_symmetric`
+    pub fn set_secret_key(&mut self, secret_key: &SecretKey) -> Result<()> {
+        let ret = unsafe { Encryptor_SetSecretKey(self.ptr, secret_key.ptr()) };
+        check_hresult!(ret, "setting the secret key of the encryptor");
+        Ok(())
     }
 
     pub fn encrypt(
@@ -36,9 +85,111 @@ impl Encryptor {
         let ct = Ciphertext::create_in_pool_of_plain_text(plain_text)?;
         let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Ciphertext_Pool(ct.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the cipher text memory pool");
+        check_hresult!(ret, "fetching the cipher text memory pool");
         let ret = unsafe { Encryptor_Encrypt(self.ptr, plain_text.ptr(), ct.ptr(), mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error encrypting");
+        check_hresult!(ret, "encrypting");
+        Ok(ct)
+    }
+
+    /// Encrypt zero at the level of `parms_id` or at the first level of the
+    /// chain, with the public key or, when the encryptor has none, with the
+    /// secret key
+    pub fn encrypt_zero(&self, parms_id: Option<ParmsId>) -> Result<Ciphertext> {
+        if !self.has_public_key {
+            return self.encrypt_zero_symmetric(parms_id);
+        }
+        let ct = Ciphertext::create()?;
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(ct.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = match parms_id {
+            Some(mut parms_id) => unsafe {
+                Encryptor_EncryptZero1(self.ptr, parms_id.as_mut_ptr(), ct.ptr(), mem_pool_ptr)
+            },
+            None => unsafe { Encryptor_EncryptZero2(self.ptr, ct.ptr(), mem_pool_ptr) },
+        };
+        check_hresult!(ret, "encrypting zero");
+        Ok(ct)
+    }
+
+    /// Add a fresh encryption of zero, see `encrypt_zero`, to a cipher text
+    /// so that it cannot be linked to the original one. The result decrypts
+    /// to the same value, with a little more noise.
+    pub fn rerandomize(&self, cipher_text: &Ciphertext) -> Result<Ciphertext> {
+        let mut zero = self.encrypt_zero(Some(cipher_text.parms_id()?))?;
+        // the scale of an encryption of zero does not matter
+        zero.set_scale(&cipher_text.scale()?)?;
+        self.evaluator()?.add(cipher_text, &zero)
+    }
+
+    fn evaluator(&self) -> Result<&Evaluator> {
+        if let Some(evaluator) = self.evaluator.get() {
+            return Ok(evaluator);
+        }
+        let evaluator = Evaluator::create(&self.context)?;
+        // another thread may have won the race, its evaluator is as good
+        Ok(self.evaluator.get_or_init(|| evaluator))
+    }
+
+    /// Encrypt with the secret key
+    pub fn encrypt_symmetric(&self, plain_text: &Plaintext) -> Result<Ciphertext> {
+        self.encrypt_symmetric_with_seed(plain_text, false)
+    }
+
+    /// Encrypt with the secret key, saving the seed of the random generator
+    /// in place of the second polynomial of the cipher text, which halves
+    /// its serialized size
+    pub fn encrypt_symmetric_serializable(
+        &self,
+        plain_text: &Plaintext,
+    ) -> Result<Serializable<Ciphertext>> {
+        Ok(Serializable::new(
+            self.encrypt_symmetric_with_seed(plain_text, true)?,
+        ))
+    }
+
+    fn encrypt_symmetric_with_seed(
+        &self,
+        plain_text: &Plaintext,
+        save_seed: bool,
+    ) -> Result<Ciphertext> {
+        let ct = Ciphertext::create_in_pool_of_plain_text(plain_text)?;
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(ct.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Encryptor_EncryptSymmetric(
+                self.ptr,
+                plain_text.ptr(),
+                save_seed as c_int,
+                ct.ptr(),
+                mem_pool_ptr,
+            )
+        };
+        check_hresult!(ret, "encrypting with the secret key");
+        Ok(ct)
+    }
+
+    /// Encrypt zero with the secret key, at the level of `parms_id` or at
+    /// the first level of the chain
+    pub fn encrypt_zero_symmetric(&self, parms_id: Option<ParmsId>) -> Result<Ciphertext> {
+        let ct = Ciphertext::create()?;
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(ct.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = match parms_id {
+            Some(mut parms_id) => unsafe {
+                Encryptor_EncryptZeroSymmetric1(
+                    self.ptr,
+                    parms_id.as_mut_ptr(),
+                    0,
+                    ct.ptr(),
+                    mem_pool_ptr,
+                )
+            },
+            None => unsafe { Encryptor_EncryptZeroSymmetric2(self.ptr, 0, ct.ptr(), mem_pool_ptr) },
+        };
+        check_hresult!(ret, "encrypting zero with the secret key");
         Ok(ct)
     }
 
@@ -55,3 +206,8 @@ impl Drop for Encryptor {
         }
     }
 }
+
+// SEAL encryptors can be used concurrently: each encryption draws its own
+// random generator
+unsafe impl Send for Encryptor {}
+unsafe impl Sync for Encryptor {}
@@ -0,0 +1,194 @@
+use std::{fmt, io, os::raw::c_long};
+
+use crate::parms_id::ParmsId;
+
+pub type Result<T, E = SealError> = std::result::Result<T, E>;
+
+// The HRESULT codes returned by the SEAL C API, see `sealc/defines.h`
+const E_POINTER: u32 = 0x8000_4003;
+const E_INVALIDARG: u32 = 0x8007_0057;
+const E_OUTOFMEMORY: u32 = 0x8007_000E;
+const E_UNEXPECTED: u32 = 0x8000_FFFF;
+const COR_E_IO: u32 = 0x8013_1620;
+const COR_E_INVALIDOPERATION: u32 = 0x8013_1509;
+
+/// The errors of this crate.
+///
+/// The failures of the SEAL C API carry the name of the operation and the
+/// raw HRESULT code. SEAL maps `std::invalid_argument` to `InvalidArgument`
+/// and `std::logic_error` to `InvalidOperation`; some of them are refined
+/// to a dedicated variant when the operation makes their cause
+/// unambiguous, such as `TransparentCiphertext`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SealError {
+    /// E_POINTER: a null pointer was passed to SEAL
+    NullPointer { operation: String, code: u32 },
+    /// E_INVALIDARG: SEAL rejected an argument, e.g. operands created with
+    /// different parameters or a plain text of the wrong size
+    InvalidArgument { operation: String, code: u32 },
+    /// COR_E_INVALIDOPERATION: SEAL refused to perform the operation
+    InvalidOperation { operation: String, code: u32 },
+    /// COR_E_INVALIDOPERATION raised because the result would be a
+    /// transparent cipher text, which can be decrypted without the secret
+    /// key, e.g. when multiplying by a zero plain text
+    TransparentCiphertext { operation: String, code: u32 },
+    /// E_INVALIDARG raised because the scale of a CKKS result does not fit
+    /// in the coefficient modulus of its level
+    ScaleOutOfBounds { operation: String, code: u32 },
+    /// E_OUTOFMEMORY
+    OutOfMemory { operation: String, code: u32 },
+    /// COR_E_IO: reading or writing serialized data failed
+    Io { operation: String, code: u32 },
+    /// E_UNEXPECTED
+    Unexpected { operation: String, code: u32 },
+    /// Any other HRESULT
+    Unknown { operation: String, code: u32 },
+    /// The encryption parameters were rejected when creating the context,
+    /// with the SEAL error name and message
+    InvalidParameters { name: String, message: String },
+    /// The operands are at different levels of the modulus switching chain
+    LevelMismatch {
+        operation: String,
+        left: ParmsId,
+        right: ParmsId,
+    },
+    /// The arguments were rejected by this crate before calling SEAL
+    Invalid { operation: String, message: String },
+    /// Reading or writing a stream of serialized data failed
+    Stream {
+        operation: String,
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+impl SealError {
+    /// Decode the HRESULT returned by a SEAL C API call
+    pub(crate) fn from_hresult(ret: c_long, operation: String) -> SealError {
+        // HRESULTs are 32 bits, `long` may be wider
+        let code = ret as u32;
+        match code {
+            E_POINTER => SealError::NullPointer { operation, code },
+            E_INVALIDARG => SealError::InvalidArgument { operation, code },
+            COR_E_INVALIDOPERATION => SealError::InvalidOperation { operation, code },
+            E_OUTOFMEMORY => SealError::OutOfMemory { operation, code },
+            COR_E_IO => SealError::Io { operation, code },
+            E_UNEXPECTED => SealError::Unexpected { operation, code },
+            _ => SealError::Unknown { operation, code },
+        }
+    }
+
+    pub(crate) fn stream(operation: impl Into<String>, error: io::Error) -> SealError {
+        SealError::Stream {
+            operation: operation.into(),
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+
+    pub(crate) fn invalid(operation: impl Into<String>, message: impl Into<String>) -> SealError {
+        SealError::Invalid {
+            operation: operation.into(),
+            message: message.into(),
+        }
+    }
+
+    /// The raw HRESULT code, for the errors returned by SEAL
+    pub fn code(&self) -> Option<u32> {
+        match self {
+            SealError::NullPointer { code, .. }
+            | SealError::InvalidArgument { code, .. }
+            | SealError::InvalidOperation { code, .. }
+            | SealError::TransparentCiphertext { code, .. }
+            | SealError::ScaleOutOfBounds { code, .. }
+            | SealError::OutOfMemory { code, .. }
+            | SealError::Io { code, .. }
+            | SealError::Unexpected { code, .. }
+            | SealError::Unknown { code, .. } => Some(*code),
+            SealError::InvalidParameters { .. }
+            | SealError::LevelMismatch { .. }
+            | SealError::Invalid { .. }
+            | SealError::Stream { .. } => None,
+        }
+    }
+
+    /// The operation which failed
+    pub fn operation(&self) -> &str {
+        match self {
+            SealError::NullPointer { operation, .. }
+            | SealError::InvalidArgument { operation, .. }
+            | SealError::InvalidOperation { operation, .. }
+            | SealError::TransparentCiphertext { operation, .. }
+            | SealError::ScaleOutOfBounds { operation, .. }
+            | SealError::OutOfMemory { operation, .. }
+            | SealError::Io { operation, .. }
+            | SealError::Unexpected { operation, .. }
+            | SealError::Unknown { operation, .. }
+            | SealError::LevelMismatch { operation, .. }
+            | SealError::Invalid { operation, .. }
+            | SealError::Stream { operation, .. } => operation,
+            SealError::InvalidParameters { .. } => "creating the context",
+        }
+    }
+}
+
+impl fmt::Display for SealError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            SealError::NullPointer { .. } => "null pointer",
+            SealError::InvalidArgument { .. } => "invalid argument",
+            SealError::InvalidOperation { .. } => "invalid operation",
+            SealError::TransparentCiphertext { .. } => {
+                "transparent output: plaintext must be non zero"
+            }
+            SealError::ScaleOutOfBounds { .. } => "scale out of bounds",
+            SealError::OutOfMemory { .. } => "out of memory",
+            SealError::Io { .. } => "I/O error",
+            SealError::Unexpected { .. } => "unexpected error",
+            SealError::Unknown { .. } => "unknown error",
+            SealError::InvalidParameters { name, message } => {
+                return write!(f, "Error {}: {}", name, message)
+            }
+            SealError::LevelMismatch {
+                operation,
+                left,
+                right,
+            } => {
+                return write!(
+                    f,
+                    "Error {}: the operands are at different levels: {} and {}",
+                    operation, left, right
+                )
+            }
+            SealError::Invalid { operation, message }
+            | SealError::Stream {
+                operation, message, ..
+            } => return write!(f, "Error {}: {}", operation, message),
+        };
+        write!(
+            f,
+            "Error {}: {} (HRESULT 0x{:08X})",
+            self.operation(),
+            description,
+            self.code().unwrap_or_default()
+        )
+    }
+}
+
+impl std::error::Error for SealError {}
+
+/// Return early with the `SealError` decoded from the HRESULT returned by a
+/// SEAL C API call, unless it is `S_OK`. The remaining arguments format the
+/// name of the operation
+macro_rules! check_hresult {
+    ($ret:expr, $($operation:tt)+) => {
+        let ret = $ret;
+        if ret != 0 {
+            return Err($crate::error::SealError::from_hresult(
+                ret,
+                format!($($operation)+),
+            ));
+        }
+    };
+}
+pub(crate) use check_hresult;
@@ -1,25 +1,74 @@
-use std::os::raw::*;
-
-use anyhow::{anyhow, Result};
+use std::{os::raw::*, sync::Mutex};
 
 use crate::{
     cipher_text::Ciphertext,
     context::Context,
+    error::{check_hresult, Result, SealError},
     key_generator::{GaloisKeys, RelinearizationKeys},
+    params::Scheme,
+    parms_id::ParmsId,
     plain_text::Plaintext,
+    rotation_planner::Rotation,
     seal_bindings::*,
 };
 
 pub struct Evaluator {
     ptr: *mut ::std::os::raw::c_void,
+    scheme: Scheme,
+    // keeps the SEAL context alive
+    context: Context,
+    // the rotations of a dry run, see `RotationPlanner`
+    recorder: Option<Mutex<Vec<Rotation>>>,
 }
 
 impl Evaluator {
     pub fn create(context: &Context) -> Result<Evaluator> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Evaluator_Create(context.ptr(), &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the evaluator");
-        Ok(Evaluator { ptr })
+        check_hresult!(ret, "creating the evaluator");
+        Ok(Evaluator {
+            ptr,
+            scheme: context.parameters()?.scheme()?,
+            context: context.clone(),
+            recorder: None,
+        })
+    }
+
+    /// An evaluator which records the rotations instead of performing them:
+    /// they return a copy of their input. The other operations are performed
+    pub(crate) fn recording(context: &Context) -> Result<Evaluator> {
+        let mut evaluator = Evaluator::create(context)?;
+        evaluator.recorder = Some(Mutex::new(Vec::new()));
+        Ok(evaluator)
+    }
+
+    /// The rotations recorded so far
+    pub(crate) fn recorded_rotations(&self) -> Vec<Rotation> {
+        match &self.recorder {
+            Some(recorder) => recorder
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .clone(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Record a rotation when dry running, in which case the rotation must
+    /// not be performed. A rotation by 0 steps needs no key and is not
+    /// recorded.
+    fn record(&self, rotation: Rotation) -> bool {
+        match &self.recorder {
+            Some(recorder) => {
+                if rotation != Rotation::Steps(0) {
+                    recorder
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .push(rotation);
+                }
+                true
+            }
+            None => false,
+        }
     }
 
     #[allow(dead_code)]
@@ -33,147 +82,260 @@ impl Evaluator {
         cipher_text_b: &Ciphertext,
     ) -> Result<Ciphertext> {
         let added = Ciphertext::create_in_pool_of_cipher_text(cipher_text_a)?;
-        let ret = unsafe {
-            Evaluator_Add(
-                self.ptr,
-                cipher_text_a.ptr(),
-                cipher_text_b.ptr(),
-                added.ptr(),
-            )
-        };
-        anyhow::ensure!(ret == 0, "Error adding");
+        self.add_into(cipher_text_a, cipher_text_b, &added)?;
         Ok(added)
     }
 
+    /// `add` into `cipher_text_a` instead of a new cipher text
+    pub fn add_assign(
+        &self,
+        cipher_text_a: &mut Ciphertext,
+        cipher_text_b: &Ciphertext,
+    ) -> Result<()> {
+        self.add_into(cipher_text_a, cipher_text_b, cipher_text_a)
+    }
+
     pub fn add_plain(
         &self,
         cipher_text_a: &Ciphertext,
         plain_text_b: &Plaintext,
     ) -> Result<Ciphertext> {
         let added = Ciphertext::create_in_pool_of_cipher_text(cipher_text_a)?;
+        self.add_plain_into(cipher_text_a, plain_text_b, &added)?;
+        Ok(added)
+    }
+
+    /// `add_plain` into `cipher_text_a` instead of a new cipher text
+    pub fn add_plain_assign(
+        &self,
+        cipher_text_a: &mut Ciphertext,
+        plain_text_b: &Plaintext,
+    ) -> Result<()> {
+        self.add_plain_into(cipher_text_a, plain_text_b, cipher_text_a)
+    }
+
+    /// The sum of the cipher texts, computed in place in a single
+    /// destination instead of one new cipher text per addition
+    pub fn add_many(&self, cipher_texts: &[&Ciphertext]) -> Result<Ciphertext> {
+        let first = ensure_many("adding many", cipher_texts)?;
+        let added = Ciphertext::create_in_pool_of_cipher_text(first)?;
+        let mut ptrs: Vec<*mut c_void> = cipher_texts.iter().map(|c| c.ptr()).collect();
         let ret = unsafe {
-            Evaluator_AddPlain(
-                self.ptr,
-                cipher_text_a.ptr(),
-                plain_text_b.ptr(),
-                added.ptr(),
-            )
+            Evaluator_AddMany(self.ptr, ptrs.len() as u64, ptrs.as_mut_ptr(), added.ptr())
         };
-        anyhow::ensure!(ret == 0, "Error adding a plain text");
+        check_hresult!(ret, "adding many");
         Ok(added)
     }
 
-    pub fn mul(
+    /// BFV and BGV: the product of the cipher texts, multiplied pairwise in
+    /// a balanced tree of depth `log2(n)` and relinearized after each
+    /// multiplication
+    pub fn multiply_many(
         &self,
-        cipher_text_a: &Ciphertext,
-        cipher_text_b: &Ciphertext,
+        cipher_texts: &[&Ciphertext],
+        relinearization_keys: &RelinearizationKeys,
     ) -> Result<Ciphertext> {
-        let mul = Ciphertext::create_in_pool_of_cipher_text(cipher_text_a)?;
+        self.ensure_integer_scheme("multiplying many")?;
+        let first = ensure_many("multiplying many", cipher_texts)?;
+        let mul = Ciphertext::create_in_pool_of_cipher_text(first)?;
         let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Ciphertext_Pool(mul.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the cipher text memory pool");
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let mut ptrs: Vec<*mut c_void> = cipher_texts.iter().map(|c| c.ptr()).collect();
         let ret = unsafe {
-            Evaluator_Multiply(
+            Evaluator_MultiplyMany(
                 self.ptr,
-                cipher_text_a.ptr(),
-                cipher_text_b.ptr(),
+                ptrs.len() as u64,
+                ptrs.as_mut_ptr(),
+                relinearization_keys.ptr(),
                 mul.ptr(),
                 mem_pool_ptr,
             )
         };
-        anyhow::ensure!(ret == 0, "Error multiplying");
+        check_hresult!(ret, "multiplying many");
+        Ok(mul)
+    }
+
+    /// BFV and BGV: the cipher text to the power of `exponent`, which must
+    /// be positive, by repeated squaring: at most `2 log2(exponent)`
+    /// multiplications, each relinearized, for a multiplicative depth of
+    /// `ceil(log2(exponent))`.
+    ///
+    /// SEAL's own exponentiation allocates one copy of the cipher text per
+    /// factor, which a large exponent turns into an abort.
+    pub fn exponentiate(
+        &self,
+        cipher_text: &Ciphertext,
+        exponent: u64,
+        relinearization_keys: &RelinearizationKeys,
+    ) -> Result<Ciphertext> {
+        self.ensure_integer_scheme("exponentiating")?;
+        if exponent == 0 {
+            return Err(SealError::invalid(
+                "exponentiating",
+                "the exponent must be positive",
+            ));
+        }
+        // the cipher text to the power of 2^i for the i-th bit of the
+        // exponent, multiplied into the power when the bit is set
+        let mut square = cipher_text.clone()?;
+        let square_inplace = |square: &mut Ciphertext| -> Result<()> {
+            self.square_inplace(square)?;
+            self.relinearize_inplace(square, relinearization_keys)
+        };
+        for _ in 0..exponent.trailing_zeros() {
+            square_inplace(&mut square)?;
+        }
+        let mut power = square.clone()?;
+        // the bits above the lowest set one, which is in the power already
+        let mut bits = exponent >> exponent.trailing_zeros() >> 1;
+        while bits != 0 {
+            square_inplace(&mut square)?;
+            if bits & 1 == 1 {
+                self.mul_assign(&mut power, &square)?;
+                self.relinearize_inplace(&mut power, relinearization_keys)?;
+            }
+            bits >>= 1;
+        }
+        Ok(power)
+    }
+
+    /// Subtract `cipher_text_b` from `cipher_text_a`. Subtracting a cipher
+    /// text from itself is refused as the result would be transparent.
+    pub fn sub(
+        &self,
+        cipher_text_a: &Ciphertext,
+        cipher_text_b: &Ciphertext,
+    ) -> Result<Ciphertext> {
+        let subtracted = Ciphertext::create_in_pool_of_cipher_text(cipher_text_a)?;
+        self.sub_into(cipher_text_a, cipher_text_b, &subtracted)?;
+        Ok(subtracted)
+    }
+
+    /// `sub` into `cipher_text_a` instead of a new cipher text
+    pub fn sub_assign(
+        &self,
+        cipher_text_a: &mut Ciphertext,
+        cipher_text_b: &Ciphertext,
+    ) -> Result<()> {
+        self.sub_into(cipher_text_a, cipher_text_b, cipher_text_a)
+    }
+
+    pub fn sub_plain(
+        &self,
+        cipher_text_a: &Ciphertext,
+        plain_text_b: &Plaintext,
+    ) -> Result<Ciphertext> {
+        let subtracted = Ciphertext::create_in_pool_of_cipher_text(cipher_text_a)?;
+        self.sub_plain_into(cipher_text_a, plain_text_b, &subtracted)?;
+        Ok(subtracted)
+    }
+
+    /// `sub_plain` into `cipher_text_a` instead of a new cipher text
+    pub fn sub_plain_assign(
+        &self,
+        cipher_text_a: &mut Ciphertext,
+        plain_text_b: &Plaintext,
+    ) -> Result<()> {
+        self.sub_plain_into(cipher_text_a, plain_text_b, cipher_text_a)
+    }
+
+    /// The opposite of the cipher text, at the same level and scale
+    pub fn negate(&self, cipher_text: &Ciphertext) -> Result<Ciphertext> {
+        let negated = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
+        self.negate_into(cipher_text, &negated)?;
+        Ok(negated)
+    }
+
+    pub fn negate_inplace(&self, cipher_text: &mut Ciphertext) -> Result<()> {
+        self.negate_into(cipher_text, cipher_text)
+    }
+
+    pub fn mul(
+        &self,
+        cipher_text_a: &Ciphertext,
+        cipher_text_b: &Ciphertext,
+    ) -> Result<Ciphertext> {
+        let mul = Ciphertext::create_in_pool_of_cipher_text(cipher_text_a)?;
+        self.mul_into(cipher_text_a, cipher_text_b, &mul)?;
         Ok(mul)
     }
 
+    /// `mul` into `cipher_text_a` instead of a new cipher text
+    pub fn mul_assign(
+        &self,
+        cipher_text_a: &mut Ciphertext,
+        cipher_text_b: &Ciphertext,
+    ) -> Result<()> {
+        self.mul_into(cipher_text_a, cipher_text_b, cipher_text_a)
+    }
+
     pub fn mul_plain(
         &self,
         cipher_text_a: &Ciphertext,
         plain_text_b: &Plaintext,
     ) -> Result<Ciphertext> {
         let mul = Ciphertext::create_in_pool_of_cipher_text(cipher_text_a)?;
-        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { Ciphertext_Pool(mul.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the cipher text memory pool");
-        let ret = unsafe {
-            Evaluator_MultiplyPlain(
-                self.ptr,
-                cipher_text_a.ptr(),
-                plain_text_b.ptr(),
-                mul.ptr(),
-                mem_pool_ptr,
-            )
-        };
-        //#define COR_E_INVALIDOPERATION _HRESULT_TYPEDEF_(0x80131509L)
-        // This error is returned by 'Evaluator_MultiplyPlain' as a logic_error uniquely
-        // when the output  ciphertext is transparent, i.e. does not require a
-        // secret key to decrypt. In typical security models such transparent
-        // ciphertexts would not be  considered to be valid. Starting from the
-        // second polynomial in the output  ciphertext, this function returns
-        // '0x80131509L' if all following coefficients are  identically zero.
-        if ret == 0x80131509 {
-            return Err(anyhow!("Transparent output: plaintext must be non zero"));
-        }
-        anyhow::ensure!(ret == 0, "Error multiplying a plain text");
+        self.mul_plain_into(cipher_text_a, plain_text_b, &mul)?;
         Ok(mul)
     }
 
+    /// `mul_plain` into `cipher_text_a` instead of a new cipher text
+    pub fn mul_plain_assign(
+        &self,
+        cipher_text_a: &mut Ciphertext,
+        plain_text_b: &Plaintext,
+    ) -> Result<()> {
+        self.mul_plain_into(cipher_text_a, plain_text_b, cipher_text_a)
+    }
+
     pub fn square(&self, cipher_text: &Ciphertext) -> Result<Ciphertext> {
         let squared = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
-        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { Ciphertext_Pool(squared.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the cipher text memory pool");
-        let ret =
-            unsafe { Evaluator_Square(self.ptr, cipher_text.ptr(), squared.ptr(), mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error squaring the cipher text");
+        self.square_into(cipher_text, &squared)?;
         Ok(squared)
     }
 
+    pub fn square_inplace(&self, cipher_text: &mut Ciphertext) -> Result<()> {
+        self.square_into(cipher_text, cipher_text)
+    }
+
     pub fn relinearize(
         &self,
         cipher_text_a: &Ciphertext,
         relinearization_keys: &RelinearizationKeys,
     ) -> Result<Ciphertext> {
         let relin = Ciphertext::create_in_pool_of_cipher_text(cipher_text_a)?;
-        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { Ciphertext_Pool(relin.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the cipher text memory pool");
-        let ret = unsafe {
-            Evaluator_Relinearize(
-                self.ptr,
-                cipher_text_a.ptr(),
-                relinearization_keys.ptr(),
-                relin.ptr(),
-                mem_pool_ptr,
-            )
-        };
-        anyhow::ensure!(ret == 0, "Error relinearizing");
+        self.relinearize_into(cipher_text_a, relinearization_keys, &relin)?;
         Ok(relin)
     }
 
+    pub fn relinearize_inplace(
+        &self,
+        cipher_text: &mut Ciphertext,
+        relinearization_keys: &RelinearizationKeys,
+    ) -> Result<()> {
+        self.relinearize_into(cipher_text, relinearization_keys, cipher_text)
+    }
+
     pub fn mod_switch_to_next(&self, cipher_text: &Ciphertext) -> Result<Ciphertext> {
         let switch = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
-        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { Ciphertext_Pool(switch.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the cipher text memory pool");
-        let ret = unsafe {
-            Evaluator_ModSwitchToNext1(self.ptr, cipher_text.ptr(), switch.ptr(), mem_pool_ptr)
-        };
-        anyhow::ensure!(ret == 0, "Error performing modulus switching");
+        self.mod_switch_to_next_into(cipher_text, &switch)?;
         Ok(switch)
     }
 
+    pub fn mod_switch_to_next_inplace(&self, cipher_text: &mut Ciphertext) -> Result<()> {
+        self.mod_switch_to_next_into(cipher_text, cipher_text)
+    }
+
     pub fn mod_switch_to_next_plain_text(&self, plain_text: &Plaintext) -> Result<Plaintext> {
         let switch = Plaintext::create_in_pool_of_plain_text(plain_text)?;
         let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Plaintext_Pool(switch.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the plain text memory pool");
+        check_hresult!(ret, "fetching the plain text memory pool");
         let ret = unsafe {
             Evaluator_ModSwitchToNext1(self.ptr, plain_text.ptr(), switch.ptr(), mem_pool_ptr)
         };
-        anyhow::ensure!(
-            ret == 0,
-            "Error performing modulus switching for plain text"
-        );
+        check_hresult!(ret, "performing modulus switching for plain text");
         Ok(switch)
     }
 
@@ -196,30 +358,27 @@ impl Evaluator {
     pub fn mod_switch_to(
         &self,
         cipher_text: &Ciphertext,
-        parms_id: &mut [u64],
+        parms_id: &ParmsId,
     ) -> Result<Ciphertext> {
         let switch = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
-        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { Ciphertext_Pool(switch.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the cipher text memory pool");
-        let ret = unsafe {
-            Evaluator_ModSwitchTo1(
-                self.ptr,
-                cipher_text.ptr(),
-                parms_id.as_mut_ptr(),
-                switch.ptr(),
-                mem_pool_ptr,
-            )
-        };
-        anyhow::ensure!(ret == 0, "Error performing modulus switching");
+        self.mod_switch_to_into(cipher_text, parms_id, &switch)?;
         Ok(switch)
     }
 
+    pub fn mod_switch_to_inplace(
+        &self,
+        cipher_text: &mut Ciphertext,
+        parms_id: &ParmsId,
+    ) -> Result<()> {
+        self.mod_switch_to_into(cipher_text, parms_id, cipher_text)
+    }
+
     pub fn mod_switch_to_plain_text(
         &self,
         plain_text: &Plaintext,
-        parms_id: &mut [u64],
+        parms_id: &ParmsId,
     ) -> Result<Plaintext> {
+        let mut parms_id = *parms_id;
         let switch = Plaintext::create_in_pool_of_plain_text(plain_text)?;
         let ret = unsafe {
             Evaluator_ModSwitchTo2(
@@ -229,44 +388,668 @@ impl Evaluator {
                 switch.ptr(),
             )
         };
-        anyhow::ensure!(ret == 0, "Error performing modulus switching");
+        check_hresult!(ret, "performing modulus switching");
         Ok(switch)
     }
 
-    pub fn rescale_to_next(&self, cipher_text: &Ciphertext) -> Result<Ciphertext> {
-        let rescale = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
+    /// Reduce the modulus of the cipher text to the next one in the modulus
+    /// switching chain by simply dropping the last prime, without scaling
+    /// the cipher text down. The noise is not reduced but the cipher text
+    /// becomes smaller.
+    ///
+    /// This is only valid for the BGV and CKKS schemes: with BFV, dropping a
+    /// prime breaks the plaintext scaling and the result cannot be decrypted.
+    pub fn mod_reduce_to_next(&self, cipher_text: &Ciphertext) -> Result<Ciphertext> {
+        let reduced = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
+        self.mod_reduce_to_next_into(cipher_text, &reduced)?;
+        Ok(reduced)
+    }
+
+    pub fn mod_reduce_to_next_inplace(&self, cipher_text: &mut Ciphertext) -> Result<()> {
+        self.mod_reduce_to_next_into(cipher_text, cipher_text)
+    }
+
+    /// Reduce the modulus of the cipher text down the modulus switching
+    /// chain until it reaches the given `parms_id`.
+    /// See `mod_reduce_to_next`
+    pub fn mod_reduce_to(
+        &self,
+        cipher_text: &Ciphertext,
+        parms_id: &ParmsId,
+    ) -> Result<Ciphertext> {
+        if self.scheme == Scheme::Bfv {
+            return Err(SealError::invalid(
+                "performing modulus reduction",
+                "modulus reduction is not available for the BFV scheme",
+            ));
+        }
+        let mut parms_id = *parms_id;
+        let reduced = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
         let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { Ciphertext_Pool(rescale.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the cipher text memory pool");
+        let ret = unsafe { Ciphertext_Pool(reduced.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
         let ret = unsafe {
-            Evaluator_RescaleToNext(self.ptr, cipher_text.ptr(), rescale.ptr(), mem_pool_ptr)
+            Evaluator_ModReduceTo(
+                self.ptr,
+                cipher_text.ptr(),
+                parms_id.as_mut_ptr(),
+                reduced.ptr(),
+                mem_pool_ptr,
+            )
         };
-        anyhow::ensure!(ret == 0, "Error performing rescale to next");
+        check_hresult!(ret, "performing modulus reduction");
+        Ok(reduced)
+    }
+
+    pub fn rescale_to_next(&self, cipher_text: &Ciphertext) -> Result<Ciphertext> {
+        let rescale = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
+        self.rescale_to_next_into(cipher_text, &rescale)?;
         Ok(rescale)
     }
 
+    pub fn rescale_to_next_inplace(&self, cipher_text: &mut Ciphertext) -> Result<()> {
+        self.rescale_to_next_into(cipher_text, cipher_text)
+    }
+
     pub fn rotate(
         &self,
         cipher_text: &Ciphertext,
         shift: i32,
         galois_keys: &GaloisKeys,
     ) -> Result<Ciphertext> {
+        if self.record(Rotation::Steps(shift)) {
+            return cipher_text.clone();
+        }
         let rotate = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
+        self.rotate_into(cipher_text, shift, galois_keys, &rotate)?;
+        Ok(rotate)
+    }
+
+    pub fn rotate_inplace(
+        &self,
+        cipher_text: &mut Ciphertext,
+        shift: i32,
+        galois_keys: &GaloisKeys,
+    ) -> Result<()> {
+        if self.record(Rotation::Steps(shift)) {
+            return Ok(());
+        }
+        self.rotate_into(cipher_text, shift, galois_keys, cipher_text)
+    }
+
+    /// BFV and BGV: rotate the two rows of the batching matrix by `steps`
+    /// columns, to the left when positive
+    pub fn rotate_rows(
+        &self,
+        cipher_text: &Ciphertext,
+        steps: i32,
+        galois_keys: &GaloisKeys,
+    ) -> Result<Ciphertext> {
+        if self.record(Rotation::Steps(steps)) {
+            return cipher_text.clone();
+        }
+        let rotated = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
+        self.rotate_rows_into(cipher_text, steps, galois_keys, &rotated)?;
+        Ok(rotated)
+    }
+
+    pub fn rotate_rows_inplace(
+        &self,
+        cipher_text: &mut Ciphertext,
+        steps: i32,
+        galois_keys: &GaloisKeys,
+    ) -> Result<()> {
+        if self.record(Rotation::Steps(steps)) {
+            return Ok(());
+        }
+        self.rotate_rows_into(cipher_text, steps, galois_keys, cipher_text)
+    }
+
+    /// BFV and BGV: swap the two rows of the batching matrix
+    pub fn rotate_columns(
+        &self,
+        cipher_text: &Ciphertext,
+        galois_keys: &GaloisKeys,
+    ) -> Result<Ciphertext> {
+        if self.record(Rotation::Conjugation) {
+            return cipher_text.clone();
+        }
+        let rotated = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
+        self.rotate_columns_into(cipher_text, galois_keys, &rotated)?;
+        Ok(rotated)
+    }
+
+    pub fn rotate_columns_inplace(
+        &self,
+        cipher_text: &mut Ciphertext,
+        galois_keys: &GaloisKeys,
+    ) -> Result<()> {
+        if self.record(Rotation::Conjugation) {
+            return Ok(());
+        }
+        self.rotate_columns_into(cipher_text, galois_keys, cipher_text)
+    }
+
+    /// CKKS: the complex conjugate of every slot
+    pub fn complex_conjugate(
+        &self,
+        cipher_text: &Ciphertext,
+        galois_keys: &GaloisKeys,
+    ) -> Result<Ciphertext> {
+        if self.record(Rotation::Conjugation) {
+            return cipher_text.clone();
+        }
+        let conjugated = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
+        self.complex_conjugate_into(cipher_text, galois_keys, &conjugated)?;
+        Ok(conjugated)
+    }
+
+    pub fn complex_conjugate_inplace(
+        &self,
+        cipher_text: &mut Ciphertext,
+        galois_keys: &GaloisKeys,
+    ) -> Result<()> {
+        if self.record(Rotation::Conjugation) {
+            return Ok(());
+        }
+        self.complex_conjugate_into(cipher_text, galois_keys, cipher_text)
+    }
+
+    /// Apply the automorphism of a Galois element, of which the rotations
+    /// and the conjugation are special cases. It is not recorded in a dry
+    /// run.
+    pub(crate) fn apply_galois(
+        &self,
+        cipher_text: &Ciphertext,
+        galois_elt: u32,
+        galois_keys: &GaloisKeys,
+    ) -> Result<Ciphertext> {
+        let applied = Ciphertext::create_in_pool_of_cipher_text(cipher_text)?;
+        self.apply_galois_into(cipher_text, galois_elt, galois_keys, &applied)?;
+        Ok(applied)
+    }
+}
+
+impl Evaluator {
+    // The operations write to a `destination` which may be one of their
+    // operands: SEAL then performs them in place.
+
+    fn add_into(
+        &self,
+        cipher_text_a: &Ciphertext,
+        cipher_text_b: &Ciphertext,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        ensure_same_level("adding", cipher_text_a, cipher_text_b)?;
+        let ret = unsafe {
+            Evaluator_Add(
+                self.ptr,
+                cipher_text_a.ptr(),
+                cipher_text_b.ptr(),
+                destination.ptr(),
+            )
+        };
+        check_hresult!(ret, "adding");
+        Ok(())
+    }
+
+    fn add_plain_into(
+        &self,
+        cipher_text_a: &Ciphertext,
+        plain_text_b: &Plaintext,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        ensure_same_level_plain("adding a plain text", cipher_text_a, plain_text_b)?;
+        let ret = unsafe {
+            Evaluator_AddPlain(
+                self.ptr,
+                cipher_text_a.ptr(),
+                plain_text_b.ptr(),
+                destination.ptr(),
+            )
+        };
+        check_hresult!(ret, "adding a plain text");
+        Ok(())
+    }
+
+    fn sub_into(
+        &self,
+        cipher_text_a: &Ciphertext,
+        cipher_text_b: &Ciphertext,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        ensure_same_level("subtracting", cipher_text_a, cipher_text_b)?;
+        let ret = unsafe {
+            Evaluator_Sub(
+                self.ptr,
+                cipher_text_a.ptr(),
+                cipher_text_b.ptr(),
+                destination.ptr(),
+            )
+        };
+        if ret != 0 {
+            let error = match SealError::from_hresult(ret, "subtracting".to_string()) {
+                SealError::InvalidOperation { operation, code } => {
+                    SealError::TransparentCiphertext { operation, code }
+                }
+                error => error,
+            };
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    fn sub_plain_into(
+        &self,
+        cipher_text_a: &Ciphertext,
+        plain_text_b: &Plaintext,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        ensure_same_level_plain("subtracting a plain text", cipher_text_a, plain_text_b)?;
+        let ret = unsafe {
+            Evaluator_SubPlain(
+                self.ptr,
+                cipher_text_a.ptr(),
+                plain_text_b.ptr(),
+                destination.ptr(),
+            )
+        };
+        check_hresult!(ret, "subtracting a plain text");
+        Ok(())
+    }
+
+    fn negate_into(&self, cipher_text: &Ciphertext, destination: &Ciphertext) -> Result<()> {
+        let ret = unsafe { Evaluator_Negate(self.ptr, cipher_text.ptr(), destination.ptr()) };
+        check_hresult!(ret, "negating");
+        Ok(())
+    }
+
+    fn mul_into(
+        &self,
+        cipher_text_a: &Ciphertext,
+        cipher_text_b: &Ciphertext,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        ensure_same_level("multiplying", cipher_text_a, cipher_text_b)?;
+        // read before the destination is overwritten
+        let scale = cipher_text_a.scale()? * cipher_text_b.scale()?;
+        let parms_id = cipher_text_a.parms_id()?;
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Evaluator_Multiply(
+                self.ptr,
+                cipher_text_a.ptr(),
+                cipher_text_b.ptr(),
+                destination.ptr(),
+                mem_pool_ptr,
+            )
+        };
+        if ret != 0 {
+            return Err(self.scale_error(
+                SealError::from_hresult(ret, "multiplying".to_string()),
+                scale,
+                &parms_id,
+            ));
+        }
+        Ok(())
+    }
+
+    fn mul_plain_into(
+        &self,
+        cipher_text_a: &Ciphertext,
+        plain_text_b: &Plaintext,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        ensure_same_level_plain("multiplying a plain text", cipher_text_a, plain_text_b)?;
+        // read before the destination is overwritten
+        let scale = cipher_text_a.scale()? * plain_text_b.scale()?;
+        let parms_id = cipher_text_a.parms_id()?;
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Evaluator_MultiplyPlain(
+                self.ptr,
+                cipher_text_a.ptr(),
+                plain_text_b.ptr(),
+                destination.ptr(),
+                mem_pool_ptr,
+            )
+        };
+        //#define COR_E_INVALIDOPERATION _HRESULT_TYPEDEF_(0x80131509L)
+        // This error is returned by 'Evaluator_MultiplyPlain' as a logic_error uniquely
+        // when the output  ciphertext is transparent, i.e. does not require a
+        // secret key to decrypt. In typical security models such transparent
+        // ciphertexts would not be  considered to be valid. Starting from the
+        // second polynomial in the output  ciphertext, this function returns
+        // '0x80131509L' if all following coefficients are  identically zero.
+        if ret != 0 {
+            let error = match SealError::from_hresult(ret, "multiplying a plain text".to_string()) {
+                SealError::InvalidOperation { operation, code } => {
+                    SealError::TransparentCiphertext { operation, code }
+                }
+                error => self.scale_error(error, scale, &parms_id),
+            };
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    fn square_into(&self, cipher_text: &Ciphertext, destination: &Ciphertext) -> Result<()> {
+        // read before the destination is overwritten
+        let scale = cipher_text.scale()?.powi(2);
+        let parms_id = cipher_text.parms_id()?;
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Evaluator_Square(self.ptr, cipher_text.ptr(), destination.ptr(), mem_pool_ptr)
+        };
+        if ret != 0 {
+            return Err(self.scale_error(
+                SealError::from_hresult(ret, "squaring the cipher text".to_string()),
+                scale,
+                &parms_id,
+            ));
+        }
+        Ok(())
+    }
+
+    fn relinearize_into(
+        &self,
+        cipher_text: &Ciphertext,
+        relinearization_keys: &RelinearizationKeys,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Evaluator_Relinearize(
+                self.ptr,
+                cipher_text.ptr(),
+                relinearization_keys.ptr(),
+                destination.ptr(),
+                mem_pool_ptr,
+            )
+        };
+        check_hresult!(ret, "relinearizing");
+        Ok(())
+    }
+
+    fn mod_switch_to_next_into(
+        &self,
+        cipher_text: &Ciphertext,
+        destination: &Ciphertext,
+    ) -> Result<()> {
         let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { Ciphertext_Pool(rotate.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the cipher text memory pool");
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Evaluator_ModSwitchToNext1(self.ptr, cipher_text.ptr(), destination.ptr(), mem_pool_ptr)
+        };
+        check_hresult!(ret, "performing modulus switching");
+        Ok(())
+    }
+
+    fn mod_switch_to_into(
+        &self,
+        cipher_text: &Ciphertext,
+        parms_id: &ParmsId,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        let mut parms_id = *parms_id;
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Evaluator_ModSwitchTo1(
+                self.ptr,
+                cipher_text.ptr(),
+                parms_id.as_mut_ptr(),
+                destination.ptr(),
+                mem_pool_ptr,
+            )
+        };
+        check_hresult!(ret, "performing modulus switching");
+        Ok(())
+    }
+
+    fn mod_reduce_to_next_into(
+        &self,
+        cipher_text: &Ciphertext,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        if self.scheme == Scheme::Bfv {
+            return Err(SealError::invalid(
+                "performing modulus reduction",
+                "modulus reduction is not available for the BFV scheme",
+            ));
+        }
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Evaluator_ModReduceToNext(self.ptr, cipher_text.ptr(), destination.ptr(), mem_pool_ptr)
+        };
+        check_hresult!(ret, "performing modulus reduction");
+        Ok(())
+    }
+
+    fn rescale_to_next_into(
+        &self,
+        cipher_text: &Ciphertext,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Evaluator_RescaleToNext(self.ptr, cipher_text.ptr(), destination.ptr(), mem_pool_ptr)
+        };
+        check_hresult!(ret, "performing rescale to next");
+        Ok(())
+    }
+
+    fn rotate_into(
+        &self,
+        cipher_text: &Ciphertext,
+        shift: i32,
+        galois_keys: &GaloisKeys,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
         let ret = unsafe {
             Evaluator_RotateVector(
                 self.ptr,
                 cipher_text.ptr(),
                 shift,
                 galois_keys.ptr(),
-                rotate.ptr(),
+                destination.ptr(),
                 mem_pool_ptr,
             )
         };
-        anyhow::ensure!(ret == 0, "Error performing vector rotation");
-        Ok(rotate)
+        check_hresult!(ret, "performing vector rotation");
+        Ok(())
+    }
+
+    fn rotate_rows_into(
+        &self,
+        cipher_text: &Ciphertext,
+        steps: i32,
+        galois_keys: &GaloisKeys,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Evaluator_RotateRows(
+                self.ptr,
+                cipher_text.ptr(),
+                steps,
+                galois_keys.ptr(),
+                destination.ptr(),
+                mem_pool_ptr,
+            )
+        };
+        check_hresult!(ret, "performing rows rotation");
+        Ok(())
+    }
+
+    fn rotate_columns_into(
+        &self,
+        cipher_text: &Ciphertext,
+        galois_keys: &GaloisKeys,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Evaluator_RotateColumns(
+                self.ptr,
+                cipher_text.ptr(),
+                galois_keys.ptr(),
+                destination.ptr(),
+                mem_pool_ptr,
+            )
+        };
+        check_hresult!(ret, "performing columns rotation");
+        Ok(())
+    }
+
+    fn complex_conjugate_into(
+        &self,
+        cipher_text: &Ciphertext,
+        galois_keys: &GaloisKeys,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Evaluator_ComplexConjugate(
+                self.ptr,
+                cipher_text.ptr(),
+                galois_keys.ptr(),
+                destination.ptr(),
+                mem_pool_ptr,
+            )
+        };
+        check_hresult!(ret, "performing complex conjugation");
+        Ok(())
+    }
+
+    fn apply_galois_into(
+        &self,
+        cipher_text: &Ciphertext,
+        galois_elt: u32,
+        galois_keys: &GaloisKeys,
+        destination: &Ciphertext,
+    ) -> Result<()> {
+        let mut mem_pool_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { Ciphertext_Pool(destination.ptr(), &mut mem_pool_ptr) };
+        check_hresult!(ret, "fetching the cipher text memory pool");
+        let ret = unsafe {
+            Evaluator_ApplyGalois(
+                self.ptr,
+                cipher_text.ptr(),
+                galois_elt,
+                galois_keys.ptr(),
+                destination.ptr(),
+                mem_pool_ptr,
+            )
+        };
+        check_hresult!(ret, "applying the galois element {}", galois_elt);
+        Ok(())
+    }
+}
+
+/// Check that both cipher texts are at the same level of the modulus
+/// switching chain: SEAL only reports a mismatch as an invalid argument
+fn ensure_same_level(operation: &str, a: &Ciphertext, b: &Ciphertext) -> Result<()> {
+    let (left, right) = (a.parms_id()?, b.parms_id()?);
+    if left != right {
+        return Err(SealError::LevelMismatch {
+            operation: operation.to_string(),
+            left,
+            right,
+        });
+    }
+    Ok(())
+}
+
+/// Check that there is at least one cipher text and that they are all at
+/// the same level, and return the first one
+fn ensure_many<'a>(operation: &str, cipher_texts: &[&'a Ciphertext]) -> Result<&'a Ciphertext> {
+    let (first, others) = cipher_texts
+        .split_first()
+        .ok_or_else(|| SealError::invalid(operation, "there are no cipher texts"))?;
+    for other in others {
+        ensure_same_level(operation, first, other)?;
+    }
+    Ok(first)
+}
+
+/// Check that a CKKS plain text is at the level of the cipher text. BFV and
+/// BGV plain texts are not bound to a level
+fn ensure_same_level_plain(operation: &str, a: &Ciphertext, b: &Plaintext) -> Result<()> {
+    let (left, right) = (a.parms_id()?, b.parms_id()?);
+    if !right.is_zero() && left != right {
+        return Err(SealError::LevelMismatch {
+            operation: operation.to_string(),
+            left,
+            right,
+        });
+    }
+    Ok(())
+}
+
+impl Evaluator {
+    /// SEAL only supports some operations for BFV and BGV
+    fn ensure_integer_scheme(&self, operation: &str) -> Result<()> {
+        if self.scheme == Scheme::Ckks {
+            return Err(SealError::invalid(
+                operation,
+                "the operation is not supported by CKKS",
+            ));
+        }
+        Ok(())
+    }
+
+    /// SEAL reports a CKKS scale which does not fit in the coefficient
+    /// modulus of the level as an invalid argument: use the dedicated
+    /// variant when this is the cause of the error
+    fn scale_error(&self, error: SealError, scale: f64, parms_id: &ParmsId) -> SealError {
+        match error {
+            SealError::InvalidArgument { operation, code }
+                if self.scheme == Scheme::Ckks && !self.scale_fits(scale, parms_id) =>
+            {
+                SealError::ScaleOutOfBounds { operation, code }
+            }
+            error => error,
+        }
+    }
+
+    /// The bound checked by SEAL: the bit count of the scale must be lower
+    /// than the bit count of the coefficient modulus of the level
+    fn scale_fits(&self, scale: f64, parms_id: &ParmsId) -> bool {
+        // when the level cannot be found, the scale is not the problem
+        let bit_count = self
+            .context
+            .context_data(parms_id)
+            .ok()
+            .flatten()
+            .and_then(|context_data| context_data.total_coeff_modulus_bit_count().ok());
+        match bit_count {
+            Some(bit_count) => scale > 0.0 && (scale.log2() as u32) < bit_count,
+            None => true,
+        }
     }
 }
 
@@ -283,3 +1066,7 @@ impl Drop for Evaluator {
         }
     }
 }
+
+// SEAL evaluators can be used concurrently
+unsafe impl Send for Evaluator {}
+unsafe impl Sync for Evaluator {}
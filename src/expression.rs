@@ -0,0 +1,358 @@
+use std::{
+    ops::{Add, Mul, Neg, Sub},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    cipher_text::Ciphertext,
+    ckks_encoder::CKKSEncoder,
+    context::Context,
+    error::{Result, SealError},
+    evaluator::Evaluator,
+    key_generator::RelinearizationKeys,
+    params::Scheme,
+    plain_text::Plaintext,
+};
+
+/// The evaluator and the relinearization keys shared by the
+/// `EncryptedValue`s of an expression. Cloning it is cheap.
+#[derive(Clone)]
+pub struct ExpressionEvaluator {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    evaluator: Evaluator,
+    relinearization_keys: RelinearizationKeys,
+    scheme: Scheme,
+    // encodes the CKKS scalars
+    ckks_encoder: Option<Mutex<CKKSEncoder>>,
+}
+
+impl ExpressionEvaluator {
+    pub fn create(
+        context: &Context,
+        relinearization_keys: RelinearizationKeys,
+    ) -> Result<ExpressionEvaluator> {
+        let scheme = context.parameters()?.scheme()?;
+        let ckks_encoder = match scheme {
+            Scheme::Ckks => Some(Mutex::new(CKKSEncoder::create(context)?)),
+            Scheme::Bfv | Scheme::Bgv => None,
+        };
+        Ok(ExpressionEvaluator {
+            inner: Arc::new(Inner {
+                evaluator: Evaluator::create(context)?,
+                relinearization_keys,
+                scheme,
+                ckks_encoder,
+            }),
+        })
+    }
+
+    /// Wrap a cipher text to use it in an expression
+    pub fn value(&self, cipher_text: Ciphertext) -> EncryptedValue {
+        EncryptedValue {
+            cipher_text: Ok(cipher_text),
+            evaluator: self.clone(),
+        }
+    }
+
+    pub fn evaluator(&self) -> &Evaluator {
+        &self.inner.evaluator
+    }
+
+    /// Apply the operation to `cipher_text` in place, relinearizing after
+    /// the multiplication of two cipher texts
+    fn apply(&self, cipher_text: &mut Ciphertext, op: Op, operand: Operand) -> Result<()> {
+        let evaluator = &self.inner.evaluator;
+        match operand {
+            Operand::Value(value) => {
+                let other = value.cipher_text()?;
+                match op {
+                    Op::Add => evaluator.add_assign(cipher_text, other),
+                    Op::Sub => evaluator.sub_assign(cipher_text, other),
+                    Op::Mul => {
+                        evaluator.mul_assign(cipher_text, other)?;
+                        evaluator.relinearize_inplace(cipher_text, &self.inner.relinearization_keys)
+                    }
+                }
+            }
+            Operand::Plain(plain_text) => self.apply_plain(cipher_text, op, plain_text),
+            Operand::Integer(scalar) => {
+                if self.inner.scheme == Scheme::Ckks {
+                    return Err(SealError::invalid(
+                        op.operation(),
+                        "integer scalars are only supported by BFV and BGV, use a f64",
+                    ));
+                }
+                self.apply_plain(cipher_text, op, &Plaintext::create_constant(scalar)?)
+            }
+            Operand::Real(scalar) => {
+                let plain_text = self.encode_real(cipher_text, op, scalar)?;
+                self.apply_plain(cipher_text, op, &plain_text)
+            }
+        }
+    }
+
+    fn apply_plain(
+        &self,
+        cipher_text: &mut Ciphertext,
+        op: Op,
+        plain_text: &Plaintext,
+    ) -> Result<()> {
+        let evaluator = &self.inner.evaluator;
+        match op {
+            Op::Add => evaluator.add_plain_assign(cipher_text, plain_text),
+            Op::Sub => evaluator.sub_plain_assign(cipher_text, plain_text),
+            Op::Mul => evaluator.mul_plain_assign(cipher_text, plain_text),
+        }
+    }
+
+    /// Encode a CKKS scalar at the level and the scale of the cipher text
+    fn encode_real(&self, cipher_text: &Ciphertext, op: Op, scalar: f64) -> Result<Plaintext> {
+        let ckks_encoder = self.inner.ckks_encoder.as_ref().ok_or_else(|| {
+            SealError::invalid(
+                op.operation(),
+                "real scalars are only supported by CKKS, use a u64",
+            )
+        })?;
+        let plain_text = ckks_encoder
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .encode_value(&scalar, &cipher_text.scale()?)?;
+        self.inner
+            .evaluator
+            .mod_switch_to_plain_text(&plain_text, &cipher_text.parms_id()?)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+}
+
+impl Op {
+    fn operation(&self) -> &'static str {
+        match self {
+            Op::Add => "adding",
+            Op::Sub => "subtracting",
+            Op::Mul => "multiplying",
+        }
+    }
+}
+
+enum Operand<'a> {
+    Value(&'a EncryptedValue),
+    Plain(&'a Plaintext),
+    Integer(u64),
+    Real(f64),
+}
+
+/// A cipher text in an arithmetic expression, e.g. `(&a + &b) * &c - 1u64`.
+///
+/// The operators take cipher texts, plain texts and scalars: `u64` for BFV
+/// and BGV, `f64` for CKKS, encoded at the level and the scale of the
+/// cipher text. Multiplying two cipher texts relinearizes the result; CKKS
+/// results are not rescaled, see `rescale_to_next`.
+///
+/// The operators cannot fail: the first error is carried through the rest
+/// of the expression and returned by `eval`. Use the `try_*` methods to
+/// check each step instead.
+///
+/// Operating on owned values reuses their cipher text instead of allocating
+/// a new one.
+pub struct EncryptedValue {
+    cipher_text: Result<Ciphertext>,
+    evaluator: ExpressionEvaluator,
+}
+
+impl EncryptedValue {
+    /// The cipher text of the expression, or its first error
+    pub fn eval(self) -> Result<Ciphertext> {
+        self.cipher_text
+    }
+
+    /// The cipher text of the expression so far, or its first error
+    pub fn cipher_text(&self) -> Result<&Ciphertext> {
+        self.cipher_text.as_ref().map_err(Clone::clone)
+    }
+
+    pub fn try_add(&self, other: &EncryptedValue) -> Result<EncryptedValue> {
+        self.try_apply(Op::Add, Operand::Value(other))
+    }
+
+    pub fn try_sub(&self, other: &EncryptedValue) -> Result<EncryptedValue> {
+        self.try_apply(Op::Sub, Operand::Value(other))
+    }
+
+    pub fn try_mul(&self, other: &EncryptedValue) -> Result<EncryptedValue> {
+        self.try_apply(Op::Mul, Operand::Value(other))
+    }
+
+    pub fn try_add_plain(&self, plain_text: &Plaintext) -> Result<EncryptedValue> {
+        self.try_apply(Op::Add, Operand::Plain(plain_text))
+    }
+
+    pub fn try_sub_plain(&self, plain_text: &Plaintext) -> Result<EncryptedValue> {
+        self.try_apply(Op::Sub, Operand::Plain(plain_text))
+    }
+
+    pub fn try_mul_plain(&self, plain_text: &Plaintext) -> Result<EncryptedValue> {
+        self.try_apply(Op::Mul, Operand::Plain(plain_text))
+    }
+
+    pub fn try_neg(&self) -> Result<EncryptedValue> {
+        let mut cipher_text = self.cipher_text()?.clone()?;
+        self.evaluator
+            .evaluator()
+            .negate_inplace(&mut cipher_text)?;
+        Ok(self.evaluator.value(cipher_text))
+    }
+
+    /// CKKS: divide the cipher text and its scale by the last prime of its
+    /// level, usually after a multiplication
+    pub fn rescale_to_next(self) -> EncryptedValue {
+        self.map(|evaluator, cipher_text| {
+            evaluator.evaluator().rescale_to_next_inplace(cipher_text)
+        })
+    }
+
+    fn try_apply(&self, op: Op, operand: Operand) -> Result<EncryptedValue> {
+        let mut cipher_text = self.cipher_text()?.clone()?;
+        self.evaluator.apply(&mut cipher_text, op, operand)?;
+        Ok(self.evaluator.value(cipher_text))
+    }
+
+    fn apply(self, op: Op, operand: Operand) -> EncryptedValue {
+        self.map(|evaluator, cipher_text| evaluator.apply(cipher_text, op, operand))
+    }
+
+    /// Transform the cipher text in place, unless the expression already
+    /// failed
+    fn map<F>(mut self, f: F) -> EncryptedValue
+    where
+        F: FnOnce(&ExpressionEvaluator, &mut Ciphertext) -> Result<()>,
+    {
+        if let Ok(cipher_text) = &mut self.cipher_text {
+            if let Err(error) = f(&self.evaluator, cipher_text) {
+                self.cipher_text = Err(error);
+            }
+        }
+        self
+    }
+}
+
+/// Implement an operator for the owned and borrowed values and operands
+macro_rules! impl_op {
+    ($trait:ident, $method:ident, $op:expr) => {
+        impl $trait<&EncryptedValue> for EncryptedValue {
+            type Output = EncryptedValue;
+
+            fn $method(self, other: &EncryptedValue) -> EncryptedValue {
+                self.apply($op, Operand::Value(other))
+            }
+        }
+
+        impl $trait<EncryptedValue> for EncryptedValue {
+            type Output = EncryptedValue;
+
+            fn $method(self, other: EncryptedValue) -> EncryptedValue {
+                self.apply($op, Operand::Value(&other))
+            }
+        }
+
+        impl $trait<&EncryptedValue> for &EncryptedValue {
+            type Output = EncryptedValue;
+
+            fn $method(self, other: &EncryptedValue) -> EncryptedValue {
+                from_result(self, self.try_apply($op, Operand::Value(other)))
+            }
+        }
+
+        impl $trait<EncryptedValue> for &EncryptedValue {
+            type Output = EncryptedValue;
+
+            fn $method(self, other: EncryptedValue) -> EncryptedValue {
+                from_result(self, self.try_apply($op, Operand::Value(&other)))
+            }
+        }
+
+        impl $trait<&Plaintext> for EncryptedValue {
+            type Output = EncryptedValue;
+
+            fn $method(self, plain_text: &Plaintext) -> EncryptedValue {
+                self.apply($op, Operand::Plain(plain_text))
+            }
+        }
+
+        impl $trait<&Plaintext> for &EncryptedValue {
+            type Output = EncryptedValue;
+
+            fn $method(self, plain_text: &Plaintext) -> EncryptedValue {
+                from_result(self, self.try_apply($op, Operand::Plain(plain_text)))
+            }
+        }
+
+        impl $trait<u64> for EncryptedValue {
+            type Output = EncryptedValue;
+
+            fn $method(self, scalar: u64) -> EncryptedValue {
+                self.apply($op, Operand::Integer(scalar))
+            }
+        }
+
+        impl $trait<u64> for &EncryptedValue {
+            type Output = EncryptedValue;
+
+            fn $method(self, scalar: u64) -> EncryptedValue {
+                from_result(self, self.try_apply($op, Operand::Integer(scalar)))
+            }
+        }
+
+        impl $trait<f64> for EncryptedValue {
+            type Output = EncryptedValue;
+
+            fn $method(self, scalar: f64) -> EncryptedValue {
+                self.apply($op, Operand::Real(scalar))
+            }
+        }
+
+        impl $trait<f64> for &EncryptedValue {
+            type Output = EncryptedValue;
+
+            fn $method(self, scalar: f64) -> EncryptedValue {
+                from_result(self, self.try_apply($op, Operand::Real(scalar)))
+            }
+        }
+    };
+}
+
+impl_op!(Add, add, Op::Add);
+impl_op!(Sub, sub, Op::Sub);
+impl_op!(Mul, mul, Op::Mul);
+
+impl Neg for EncryptedValue {
+    type Output = EncryptedValue;
+
+    fn neg(self) -> EncryptedValue {
+        self.map(|evaluator, cipher_text| evaluator.evaluator().negate_inplace(cipher_text))
+    }
+}
+
+impl Neg for &EncryptedValue {
+    type Output = EncryptedValue;
+
+    fn neg(self) -> EncryptedValue {
+        from_result(self, self.try_neg())
+    }
+}
+
+/// Carry the error of a checked operation in the expression
+fn from_result(value: &EncryptedValue, result: Result<EncryptedValue>) -> EncryptedValue {
+    result.unwrap_or_else(|error| EncryptedValue {
+        cipher_text: Err(error),
+        evaluator: value.evaluator.clone(),
+    })
+}
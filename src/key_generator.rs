@@ -1,8 +1,16 @@
-use std::{convert::TryInto, os::raw::*};
-
-use anyhow::{Error, Result};
-
-use crate::{context::Context, seal_bindings::*};
+use std::{
+    convert::TryInto,
+    io::{Read, Write},
+    os::raw::*,
+};
+
+use crate::{
+    context::{creation_lock, Context},
+    error::{check_hresult, Result, SealError},
+    parms_id::ParmsId,
+    seal_bindings::*,
+    serialization::{self, ComprMode, LoadFn, Serializable},
+};
 
 pub struct PublicKey {
     ptr: *mut ::std::os::raw::c_void,
@@ -13,55 +21,116 @@ impl PublicKey {
         self.ptr
     }
 
+    /// The id of the key level of the context the key was generated for
+    pub fn parms_id(&self) -> Result<ParmsId> {
+        ParmsId::read(self.ptr, PublicKey_ParmsId, "public key")
+    }
+
+    pub fn clone(&self) -> Result<PublicKey> {
+        let mut ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { PublicKey_Create2(self.ptr, &mut ptr) };
+        check_hresult!(ret, "cloning the public key");
+        Ok(PublicKey { ptr })
+    }
+
+    /// The coefficients of the key, which is a cipher text
+    fn data(&self) -> Result<Vec<u64>> {
+        // the cipher text is owned by the key
+        let mut data_ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { PublicKey_Data(self.ptr, &mut data_ptr) };
+        check_hresult!(ret, "getting the public key data");
+        let (mut size, mut degree, mut coeff_modulus_size) = (0u64, 0u64, 0u64);
+        let ret = unsafe { Ciphertext_Size(data_ptr, &mut size) };
+        check_hresult!(ret, "getting the public key size");
+        let ret = unsafe { Ciphertext_PolyModulusDegree(data_ptr, &mut degree) };
+        check_hresult!(ret, "getting the public key degree");
+        let ret = unsafe { Ciphertext_CoeffModulusSize(data_ptr, &mut coeff_modulus_size) };
+        check_hresult!(ret, "getting the public key coeff modulus size");
+        (0..size * degree * coeff_modulus_size)
+            .map(|index| {
+                let mut coeff = 0u64;
+                let ret = unsafe { Ciphertext_GetDataAt1(data_ptr, index, &mut coeff) };
+                check_hresult!(ret, "reading the public key data at {}", index);
+                Ok(coeff)
+            })
+            .collect()
+    }
+
     pub fn save(&self) -> Result<Vec<u8>> {
-        let compression_mode = 1u8; //bzip
-        let mut uncompressed_size: i64 = 0;
-        let ret = unsafe { PublicKey_SaveSize(self.ptr, compression_mode, &mut uncompressed_size) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error estimating the save size for the public key"
-        );
-        let mut actual_size = 0i64;
-        let mut bytes: Vec<u8> = vec![0u8; uncompressed_size as usize];
-        let ret = unsafe {
-            PublicKey_Save(
-                self.ptr,
-                bytes.as_mut_ptr(),
-                uncompressed_size as u64,
-                compression_mode,
-                &mut actual_size,
-            )
-        };
-        anyhow::ensure!(ret == 0, "Error saving the public key");
-        // if compression is 'on', the actual size
-        // will be less than the uncompressed_size
-        Ok(bytes[0..actual_size as usize].to_vec())
+        self.save_with(ComprMode::default_mode()?)
+    }
+
+    pub fn save_with(&self, mode: ComprMode) -> Result<Vec<u8>> {
+        serialization::save(
+            self.ptr,
+            mode,
+            PublicKey_SaveSize,
+            PublicKey_Save,
+            "public key",
+        )
+    }
+
+    /// Write the object saved with the default compression mode and return
+    /// the number of bytes written. The C API only saves to memory: the
+    /// object is saved in a buffer of its worst case size, then written.
+    pub fn save_to<W: Write>(&self, writer: W) -> Result<usize> {
+        self.save_to_with(writer, ComprMode::default_mode()?)
+    }
+
+    /// `save_to` with the given compression mode, which must be supported
+    /// by this build
+    pub fn save_to_with<W: Write>(&self, writer: W, mode: ComprMode) -> Result<usize> {
+        serialization::write(writer, &self.save_with(mode)?, "public key")
+    }
+
+    /// Read an object written by `save_to`, without reading past it. The C
+    /// API only loads from memory: the object is read in a buffer first.
+    pub fn load_from<R: Read>(context: &Context, reader: R) -> Result<PublicKey> {
+        PublicKey::load(context, &mut serialization::read(reader, "public key")?)
+    }
+
+    /// Whether the public key is valid for the parameters of the context
+    pub fn is_valid_for(&self, context: &Context) -> Result<bool> {
+        serialization::is_valid_for(
+            self.ptr,
+            context,
+            ValCheck_PublicKey_IsValidFor,
+            "public key",
+        )
     }
 
     pub fn load(context: &Context, bytes: &mut [u8]) -> Result<PublicKey> {
+        PublicKey::load_with(context, bytes, PublicKey_Load)
+    }
+
+    /// Load the public key without checking it against the parameters.
+    ///
+    /// # Safety
+    ///
+    /// SEAL trusts the loaded data: the bytes must come from a trusted
+    /// source, or the public key must be checked with `is_valid_for` before
+    /// use, since SEAL reads out of bounds on malformed data.
+    pub unsafe fn unsafe_load(context: &Context, bytes: &mut [u8]) -> Result<PublicKey> {
+        PublicKey::load_with(context, bytes, PublicKey_UnsafeLoad)
+    }
+
+    fn load_with(context: &Context, bytes: &mut [u8], load: LoadFn) -> Result<PublicKey> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { PublicKey_Create1(&mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error instantiating public key: {}",
-            std::io::Error::last_os_error()
-        );
+        check_hresult!(ret, "instantiating public key");
+        let loaded = PublicKey { ptr };
         let mut actual_size: i64 = 0;
         let ret = unsafe {
-            PublicKey_Load(
-                ptr,
+            load(
+                loaded.ptr,
                 context.ptr(),
                 bytes.as_mut_ptr(),
                 bytes.len() as u64,
                 &mut actual_size,
             )
         };
-        anyhow::ensure!(
-            ret == 0,
-            "Error loading the public key: {}",
-            std::io::Error::last_os_error()
-        );
-        Ok(PublicKey { ptr })
+        check_hresult!(ret, "loading the public key");
+        Ok(loaded)
     }
 }
 
@@ -73,6 +142,23 @@ impl Drop for PublicKey {
     }
 }
 
+/// Keys are equal when they are at the same level with the same coefficients
+impl PartialEq for PublicKey {
+    fn eq(&self, other: &PublicKey) -> bool {
+        match (
+            self.parms_id().and_then(|id| Ok((id, self.data()?))),
+            other.parms_id().and_then(|id| Ok((id, other.data()?))),
+        ) {
+            (Ok(left), Ok(right)) => left == right,
+            _ => false,
+        }
+    }
+}
+
+// Keys are never modified once created
+unsafe impl Send for PublicKey {}
+unsafe impl Sync for PublicKey {}
+
 pub struct SecretKey {
     ptr: *mut c_void,
 }
@@ -82,55 +168,86 @@ impl SecretKey {
         self.ptr
     }
 
+    /// The id of the key level of the context the key was generated for
+    pub fn parms_id(&self) -> Result<ParmsId> {
+        ParmsId::read(self.ptr, SecretKey_ParmsId, "secret key")
+    }
+
     pub fn save(&self) -> Result<Vec<u8>> {
-        let compression_mode = 1u8; //bzip
-        let mut uncompressed_size: i64 = 0;
-        let ret = unsafe { SecretKey_SaveSize(self.ptr, compression_mode, &mut uncompressed_size) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error estimating the save size for the secret key"
-        );
-        let mut actual_size = 0i64;
-        let mut bytes: Vec<u8> = vec![0u8; uncompressed_size as usize];
-        let ret = unsafe {
-            SecretKey_Save(
-                self.ptr,
-                bytes.as_mut_ptr(),
-                uncompressed_size as u64,
-                compression_mode,
-                &mut actual_size,
-            )
-        };
-        anyhow::ensure!(ret == 0, "Error saving the secret key");
-        // if compression is 'on', the actual size
-        // will be less than the uncompressed_size
-        Ok(bytes[0..actual_size as usize].to_vec())
+        self.save_with(ComprMode::default_mode()?)
+    }
+
+    pub fn save_with(&self, mode: ComprMode) -> Result<Vec<u8>> {
+        serialization::save(
+            self.ptr,
+            mode,
+            SecretKey_SaveSize,
+            SecretKey_Save,
+            "secret key",
+        )
+    }
+
+    /// Write the object saved with the default compression mode and return
+    /// the number of bytes written. The C API only saves to memory: the
+    /// object is saved in a buffer of its worst case size, then written.
+    pub fn save_to<W: Write>(&self, writer: W) -> Result<usize> {
+        self.save_to_with(writer, ComprMode::default_mode()?)
+    }
+
+    /// `save_to` with the given compression mode, which must be supported
+    /// by this build
+    pub fn save_to_with<W: Write>(&self, writer: W, mode: ComprMode) -> Result<usize> {
+        serialization::write(writer, &self.save_with(mode)?, "secret key")
+    }
+
+    /// Read an object written by `save_to`, without reading past it. The C
+    /// API only loads from memory: the object is read in a buffer first.
+    pub fn load_from<R: Read>(context: &Context, reader: R) -> Result<SecretKey> {
+        SecretKey::load(context, &mut serialization::read(reader, "secret key")?)
+    }
+
+    /// Whether the secret key is valid for the parameters of the context
+    pub fn is_valid_for(&self, context: &Context) -> Result<bool> {
+        serialization::is_valid_for(
+            self.ptr,
+            context,
+            ValCheck_SecretKey_IsValidFor,
+            "secret key",
+        )
     }
 
     pub fn load(context: &Context, bytes: &mut [u8]) -> Result<SecretKey> {
+        SecretKey::load_with(context, bytes, SecretKey_Load)
+    }
+
+    /// Load the secret key without checking it against the parameters.
+    ///
+    /// # Safety
+    ///
+    /// SEAL trusts the loaded data: the bytes must come from a trusted
+    /// source, or the secret key must be checked with `is_valid_for` before
+    /// use, since SEAL reads out of bounds on malformed data.
+    pub unsafe fn unsafe_load(context: &Context, bytes: &mut [u8]) -> Result<SecretKey> {
+        SecretKey::load_with(context, bytes, SecretKey_UnsafeLoad)
+    }
+
+    fn load_with(context: &Context, bytes: &mut [u8], load: LoadFn) -> Result<SecretKey> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { SecretKey_Create1(&mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error instantiating secret key: {}",
-            std::io::Error::last_os_error()
-        );
+        check_hresult!(ret, "instantiating secret key");
+        let loaded = SecretKey { ptr };
         let mut actual_size: i64 = 0;
         let ret = unsafe {
-            SecretKey_Load(
-                ptr,
+            load(
+                loaded.ptr,
                 context.ptr(),
                 bytes.as_mut_ptr(),
                 bytes.len() as u64,
                 &mut actual_size,
             )
         };
-        anyhow::ensure!(
-            ret == 0,
-            "Error loading the secret key: {}",
-            std::io::Error::last_os_error()
-        );
-        Ok(SecretKey { ptr })
+        check_hresult!(ret, "loading the secret key");
+        Ok(loaded)
     }
 }
 
@@ -142,6 +259,10 @@ impl Drop for SecretKey {
     }
 }
 
+// Keys are never modified once created
+unsafe impl Send for SecretKey {}
+unsafe impl Sync for SecretKey {}
+
 pub struct RelinearizationKeys {
     ptr: *mut c_void,
 }
@@ -151,66 +272,96 @@ impl RelinearizationKeys {
         self.ptr
     }
 
+    /// The id of the key level of the context the keys were generated for
+    pub fn parms_id(&self) -> Result<ParmsId> {
+        ParmsId::read(self.ptr, KSwitchKeys_GetParmsId, "relinearization keys")
+    }
+
     pub fn save(&self) -> Result<Vec<u8>> {
-        let compression_mode = 1u8; //bzip
-        let mut uncompressed_size: i64 = 0;
-        let ret =
-            unsafe { KSwitchKeys_SaveSize(self.ptr, compression_mode, &mut uncompressed_size) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error estimating the save size for the relinearization keys"
-        );
-        let mut actual_size = 0i64;
-        let mut bytes: Vec<u8> = vec![0u8; uncompressed_size as usize];
-        let ret = unsafe {
-            KSwitchKeys_Save(
-                self.ptr,
-                bytes.as_mut_ptr(),
-                uncompressed_size as u64,
-                compression_mode,
-                &mut actual_size,
-            )
-        };
-        anyhow::ensure!(ret == 0, "Error saving the relinearization keys");
-        // if compression is 'on', the actual size
-        // will be less than the uncompressed_size
-        Ok(bytes[0..actual_size as usize].to_vec())
+        self.save_with(ComprMode::default_mode()?)
+    }
+
+    pub fn save_with(&self, mode: ComprMode) -> Result<Vec<u8>> {
+        serialization::save(
+            self.ptr,
+            mode,
+            KSwitchKeys_SaveSize,
+            KSwitchKeys_Save,
+            "relinearization keys",
+        )
+    }
+
+    /// Write the object saved with the default compression mode and return
+    /// the number of bytes written. The C API only saves to memory: the
+    /// object is saved in a buffer of its worst case size, then written.
+    pub fn save_to<W: Write>(&self, writer: W) -> Result<usize> {
+        self.save_to_with(writer, ComprMode::default_mode()?)
+    }
+
+    /// `save_to` with the given compression mode, which must be supported
+    /// by this build
+    pub fn save_to_with<W: Write>(&self, writer: W, mode: ComprMode) -> Result<usize> {
+        serialization::write(writer, &self.save_with(mode)?, "relinearization keys")
+    }
+
+    /// Read an object written by `save_to`, without reading past it. The C
+    /// API only loads from memory: the object is read in a buffer first.
+    pub fn load_from<R: Read>(context: &Context, reader: R) -> Result<RelinearizationKeys> {
+        RelinearizationKeys::load(
+            context,
+            &mut serialization::read(reader, "relinearization keys")?,
+        )
+    }
+
+    /// Whether the relinearization keys are valid for the parameters of the context
+    pub fn is_valid_for(&self, context: &Context) -> Result<bool> {
+        serialization::is_valid_for(
+            self.ptr,
+            context,
+            ValCheck_RelinKeys_IsValidFor,
+            "relinearization keys",
+        )
     }
 
     pub fn load(context: &Context, bytes: &mut [u8]) -> Result<RelinearizationKeys> {
+        RelinearizationKeys::load_with(context, bytes, KSwitchKeys_Load)
+    }
+
+    /// Load the relinearization keys without checking them against the
+    /// parameters.
+    ///
+    /// # Safety
+    ///
+    /// SEAL trusts the loaded data: the bytes must come from a trusted
+    /// source, or the relinearization keys must be checked with `is_valid_for` before
+    /// use, since SEAL reads out of bounds on malformed data.
+    pub unsafe fn unsafe_load(context: &Context, bytes: &mut [u8]) -> Result<RelinearizationKeys> {
+        RelinearizationKeys::load_with(context, bytes, KSwitchKeys_UnsafeLoad)
+    }
+
+    fn load_with(context: &Context, bytes: &mut [u8], load: LoadFn) -> Result<RelinearizationKeys> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { KSwitchKeys_Create1(&mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error instantiating relinearization keys: {}",
-            std::io::Error::last_os_error()
-        );
+        check_hresult!(ret, "instantiating relinearization keys");
+        let loaded = RelinearizationKeys { ptr };
         let mut actual_size: i64 = 0;
         let ret = unsafe {
-            KSwitchKeys_Load(
-                ptr,
+            load(
+                loaded.ptr,
                 context.ptr(),
                 bytes.as_mut_ptr(),
                 bytes.len() as u64,
                 &mut actual_size,
             )
         };
-        anyhow::ensure!(
-            ret == 0,
-            "Error loading the relinearization keys: {}",
-            std::io::Error::last_os_error()
-        );
-        Ok(RelinearizationKeys { ptr })
+        check_hresult!(ret, "loading the relinearization keys");
+        Ok(loaded)
     }
 
     pub fn clone(&self) -> Result<RelinearizationKeys> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { KSwitchKeys_Create2(self.ptr(), &mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error cloning the relinearization text: {}",
-            std::io::Error::last_os_error()
-        );
+        check_hresult!(ret, "cloning the relinearization keys");
         Ok(RelinearizationKeys { ptr })
     }
 }
@@ -222,7 +373,7 @@ impl PartialEq for RelinearizationKeys {
 }
 
 impl TryInto<Vec<u8>> for RelinearizationKeys {
-    type Error = Error;
+    type Error = SealError;
 
     fn try_into(self) -> Result<Vec<u8>, Self::Error> {
         self.save()
@@ -237,6 +388,10 @@ impl Drop for RelinearizationKeys {
     }
 }
 
+// Keys are never modified once created
+unsafe impl Send for RelinearizationKeys {}
+unsafe impl Sync for RelinearizationKeys {}
+
 pub struct GaloisKeys {
     ptr: *mut c_void,
 }
@@ -246,72 +401,161 @@ impl GaloisKeys {
         self.ptr
     }
 
-    pub fn save(&self) -> Result<Vec<u8>> {
-        let compression_mode = 1u8; //bzip
-        let mut uncompressed_size: i64 = 0;
-        let ret =
-            unsafe { KSwitchKeys_SaveSize(self.ptr, compression_mode, &mut uncompressed_size) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error estimating the save size for the galois keys"
+    /// Keys without any key, only usable in a dry run
+    pub(crate) fn empty() -> Result<GaloisKeys> {
+        let mut ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { KSwitchKeys_Create1(&mut ptr) };
+        check_hresult!(ret, "instantiating galois keys");
+        Ok(GaloisKeys { ptr })
+    }
+
+    /// The id of the key level of the context the keys were generated for
+    pub fn parms_id(&self) -> Result<ParmsId> {
+        ParmsId::read(self.ptr, KSwitchKeys_GetParmsId, "Galois keys")
+    }
+
+    /// The index of the key of a Galois element in the keys
+    pub fn index_of(galois_elt: u32) -> Result<usize> {
+        let mut index = 0u64;
+        let ret = unsafe { GaloisKeys_GetIndex(galois_elt, &mut index) };
+        check_hresult!(
+            ret,
+            "getting the index of the galois element {}",
+            galois_elt
         );
-        let mut actual_size = 0i64;
-        let mut bytes: Vec<u8> = vec![0u8; uncompressed_size as usize];
+        Ok(index as usize)
+    }
+
+    /// Whether the keys hold the key of a Galois element, see
+    /// `Context::galois_elt_from_step` to find the element of a rotation
+    pub fn has_key(&self, galois_elt: u32) -> Result<bool> {
+        // the elements are odd
+        if galois_elt & 1 == 0 {
+            return Ok(false);
+        }
+        let index = GaloisKeys::index_of(galois_elt)?;
+        let mut raw_size = 0u64;
+        let ret = unsafe { KSwitchKeys_RawSize(self.ptr, &mut raw_size) };
+        check_hresult!(ret, "getting the size of the galois keys");
+        if index as u64 >= raw_size {
+            return Ok(false);
+        }
+        // only the count is read when the list is null
+        let mut count = 0u64;
         let ret = unsafe {
-            KSwitchKeys_Save(
-                self.ptr,
-                bytes.as_mut_ptr(),
-                uncompressed_size as u64,
-                compression_mode,
-                &mut actual_size,
-            )
+            KSwitchKeys_GetKeyList(self.ptr, index as u64, &mut count, std::ptr::null_mut())
         };
-        anyhow::ensure!(ret == 0, "Error saving the galois keys");
-        // if compression is 'on', the actual size
-        // will be less than the uncompressed_size
-        Ok(bytes[0..actual_size as usize].to_vec())
+        check_hresult!(ret, "getting the key of the galois element {}", galois_elt);
+        Ok(count > 0)
+    }
+
+    /// The Galois elements the keys hold a key for, sorted
+    pub(crate) fn galois_elts(&self) -> Result<Vec<u32>> {
+        let mut raw_size = 0u64;
+        let ret = unsafe { KSwitchKeys_RawSize(self.ptr, &mut raw_size) };
+        check_hresult!(ret, "getting the size of the galois keys");
+        let mut galois_elts = Vec::new();
+        for index in 0..raw_size {
+            let mut count = 0u64;
+            let ret = unsafe {
+                KSwitchKeys_GetKeyList(self.ptr, index, &mut count, std::ptr::null_mut())
+            };
+            check_hresult!(ret, "getting the galois key at index {}", index);
+            if count > 0 {
+                // the inverse of `index_of`
+                galois_elts.push((2 * index + 1) as u32);
+            }
+        }
+        Ok(galois_elts)
+    }
+
+    pub fn save(&self) -> Result<Vec<u8>> {
+        self.save_with(ComprMode::default_mode()?)
+    }
+
+    pub fn save_with(&self, mode: ComprMode) -> Result<Vec<u8>> {
+        serialization::save(
+            self.ptr,
+            mode,
+            KSwitchKeys_SaveSize,
+            KSwitchKeys_Save,
+            "galois keys",
+        )
+    }
+
+    /// Write the object saved with the default compression mode and return
+    /// the number of bytes written. The C API only saves to memory: the
+    /// object is saved in a buffer of its worst case size, then written.
+    pub fn save_to<W: Write>(&self, writer: W) -> Result<usize> {
+        self.save_to_with(writer, ComprMode::default_mode()?)
+    }
+
+    /// `save_to` with the given compression mode, which must be supported
+    /// by this build
+    pub fn save_to_with<W: Write>(&self, writer: W, mode: ComprMode) -> Result<usize> {
+        serialization::write(writer, &self.save_with(mode)?, "galois keys")
+    }
+
+    /// Read an object written by `save_to`, without reading past it. The C
+    /// API only loads from memory: the object is read in a buffer first.
+    pub fn load_from<R: Read>(context: &Context, reader: R) -> Result<GaloisKeys> {
+        GaloisKeys::load(context, &mut serialization::read(reader, "galois keys")?)
+    }
+
+    /// Whether the Galois keys are valid for the parameters of the context
+    pub fn is_valid_for(&self, context: &Context) -> Result<bool> {
+        serialization::is_valid_for(
+            self.ptr,
+            context,
+            ValCheck_GaloisKeys_IsValidFor,
+            "Galois keys",
+        )
     }
 
     pub fn load(context: &Context, bytes: &mut [u8]) -> Result<GaloisKeys> {
+        GaloisKeys::load_with(context, bytes, KSwitchKeys_Load)
+    }
+
+    /// Load the Galois keys without checking them against the parameters.
+    ///
+    /// # Safety
+    ///
+    /// SEAL trusts the loaded data: the bytes must come from a trusted
+    /// source, or the Galois keys must be checked with `is_valid_for` before
+    /// use, since SEAL reads out of bounds on malformed data.
+    pub unsafe fn unsafe_load(context: &Context, bytes: &mut [u8]) -> Result<GaloisKeys> {
+        GaloisKeys::load_with(context, bytes, KSwitchKeys_UnsafeLoad)
+    }
+
+    fn load_with(context: &Context, bytes: &mut [u8], load: LoadFn) -> Result<GaloisKeys> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { KSwitchKeys_Create1(&mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error instantiating galois keys: {}",
-            std::io::Error::last_os_error()
-        );
+        check_hresult!(ret, "instantiating galois keys");
+        let loaded = GaloisKeys { ptr };
         let mut actual_size: i64 = 0;
         let ret = unsafe {
-            KSwitchKeys_Load(
-                ptr,
+            load(
+                loaded.ptr,
                 context.ptr(),
                 bytes.as_mut_ptr(),
                 bytes.len() as u64,
                 &mut actual_size,
             )
         };
-        anyhow::ensure!(
-            ret == 0,
-            "Error loading the galois keys: {}",
-            std::io::Error::last_os_error()
-        );
-        Ok(GaloisKeys { ptr })
+        check_hresult!(ret, "loading the galois keys");
+        Ok(loaded)
     }
 
     pub fn clone(&self) -> Result<GaloisKeys> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { KSwitchKeys_Create2(self.ptr(), &mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error cloning the galois text: {}",
-            std::io::Error::last_os_error()
-        );
+        check_hresult!(ret, "cloning the Galois keys");
         Ok(GaloisKeys { ptr })
     }
 }
 
 impl TryInto<Vec<u8>> for GaloisKeys {
-    type Error = Error;
+    type Error = SealError;
 
     fn try_into(self) -> Result<Vec<u8>, Self::Error> {
         self.save()
@@ -326,8 +570,14 @@ impl Drop for GaloisKeys {
     }
 }
 
+// Keys are never modified once created
+unsafe impl Send for GaloisKeys {}
+unsafe impl Sync for GaloisKeys {}
+
 pub struct KeyGenerator {
     ptr: *mut ::std::os::raw::c_void,
+    // keeps the SEAL context alive
+    _context: Context,
 }
 
 /// The encryption schemes in Microsoft SEAL are public key encryption schemes.
@@ -339,22 +589,52 @@ pub struct KeyGenerator {
 impl KeyGenerator {
     pub fn create(context: &Context) -> Result<KeyGenerator> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
+        let _lock = creation_lock();
         let ret = unsafe { KeyGenerator_Create1(context.ptr(), &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the key generator");
-        Ok(KeyGenerator { ptr })
+        check_hresult!(ret, "creating the key generator");
+        Ok(KeyGenerator {
+            ptr,
+            _context: context.clone(),
+        })
+    }
+
+    /// A key generator for an existing secret key, e.g. reloaded after a
+    /// restart, to derive new public, relinearization or galois keys
+    /// matching it
+    pub fn from_secret_key(context: &Context, secret_key: &SecretKey) -> Result<KeyGenerator> {
+        let mut ptr: *mut c_void = std::ptr::null_mut();
+        let _lock = creation_lock();
+        let ret = unsafe { KeyGenerator_Create2(context.ptr(), secret_key.ptr(), &mut ptr) };
+        check_hresult!(ret, "creating the key generator from the secret key");
+        Ok(KeyGenerator {
+            ptr,
+            _context: context.clone(),
+        })
     }
 
+    /// A fresh public key: each call returns a new random public key for
+    /// the same secret key, e.g. one per client
     pub fn public_key(&self) -> Result<PublicKey> {
+        self.create_public_key(false)
+    }
+
+    /// A fresh public key to send to a client, with half of its data
+    /// replaced by a seed
+    pub fn public_key_serializable(&self) -> Result<Serializable<PublicKey>> {
+        Ok(Serializable::new(self.create_public_key(true)?))
+    }
+
+    fn create_public_key(&self, save_seed: bool) -> Result<PublicKey> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { KeyGenerator_CreatePublicKey(self.ptr, 0, &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the public key");
+        let ret = unsafe { KeyGenerator_CreatePublicKey(self.ptr, save_seed as c_int, &mut ptr) };
+        check_hresult!(ret, "creating the public key");
         Ok(PublicKey { ptr })
     }
 
     pub fn secret_key(&self) -> Result<SecretKey> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { KeyGenerator_SecretKey(self.ptr, &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the secret key");
+        check_hresult!(ret, "creating the secret key");
         Ok(SecretKey { ptr })
     }
 
@@ -373,18 +653,79 @@ impl KeyGenerator {
     ///
     /// Relinearization is used similarly in both the BFV and the CKKS schemes
     pub fn relinearization_keys(&self) -> Result<RelinearizationKeys> {
+        self.create_relinearization_keys(false)
+    }
+
+    /// The relinearization keys to send to the party doing the
+    /// computations, with half of their data replaced by a seed
+    pub fn relinearization_keys_serializable(&self) -> Result<Serializable<RelinearizationKeys>> {
+        Ok(Serializable::new(self.create_relinearization_keys(true)?))
+    }
+
+    fn create_relinearization_keys(&self, save_seed: bool) -> Result<RelinearizationKeys> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { KeyGenerator_CreateRelinKeys(self.ptr, 0, &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the relinearization key");
+        let ret = unsafe { KeyGenerator_CreateRelinKeys(self.ptr, save_seed as c_int, &mut ptr) };
+        check_hresult!(ret, "creating the relinearization key");
         Ok(RelinearizationKeys { ptr })
     }
 
     /// Galois keys are used to rotate vector, similarly in both the BFV and the
     /// CKKS schemes
     pub fn galois_keys(&self) -> Result<GaloisKeys> {
+        self.create_galois_keys(false)
+    }
+
+    /// The galois keys to send to the party doing the computations, with
+    /// half of their data replaced by a seed
+    pub fn galois_keys_serializable(&self) -> Result<Serializable<GaloisKeys>> {
+        Ok(Serializable::new(self.create_galois_keys(true)?))
+    }
+
+    /// Galois keys for the rotations by the given steps only: the keys for
+    /// all the rotations are much larger
+    pub fn galois_keys_for_steps(&self, steps: &[i32]) -> Result<GaloisKeys> {
+        let mut steps = steps.to_vec();
+        let mut ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe {
+            KeyGenerator_CreateGaloisKeysFromSteps(
+                self.ptr,
+                steps.len() as u64,
+                steps.as_mut_ptr(),
+                0,
+                &mut ptr,
+            )
+        };
+        check_hresult!(ret, "creating the galois keys for the steps: {:?}", steps);
+        Ok(GaloisKeys { ptr })
+    }
+
+    /// Galois keys for the given Galois elements only, see
+    /// `Context::galois_elt_from_step`
+    pub fn galois_keys_for_elements(&self, galois_elts: &[u32]) -> Result<GaloisKeys> {
+        let mut galois_elts = galois_elts.to_vec();
         let mut ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { KeyGenerator_CreateGaloisKeysAll(self.ptr, 0, &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the galois key");
+        let ret = unsafe {
+            KeyGenerator_CreateGaloisKeysFromElts(
+                self.ptr,
+                galois_elts.len() as u64,
+                galois_elts.as_mut_ptr(),
+                0,
+                &mut ptr,
+            )
+        };
+        check_hresult!(
+            ret,
+            "creating the galois keys for the elements: {:?}",
+            galois_elts
+        );
+        Ok(GaloisKeys { ptr })
+    }
+
+    fn create_galois_keys(&self, save_seed: bool) -> Result<GaloisKeys> {
+        let mut ptr: *mut c_void = std::ptr::null_mut();
+        let ret =
+            unsafe { KeyGenerator_CreateGaloisKeysAll(self.ptr, save_seed as c_int, &mut ptr) };
+        check_hresult!(ret, "creating the galois key");
         Ok(GaloisKeys { ptr })
     }
 }
@@ -396,3 +737,7 @@ impl Drop for KeyGenerator {
         }
     }
 }
+
+// SEAL key generators lock their internal state when generating keys
+unsafe impl Send for KeyGenerator {}
+unsafe impl Sync for KeyGenerator {}
@@ -0,0 +1,378 @@
+use std::io::{Read, Write};
+
+use crate::{
+    cipher_text::Ciphertext,
+    ckks_encoder::CKKSEncoder,
+    context::Context,
+    decryptor::Decryptor,
+    encryptor::Encryptor,
+    error::{Result, SealError},
+    evaluator::Evaluator,
+    key_generator::{GaloisKeys, PublicKey, RelinearizationKeys, SecretKey},
+    params::{Params, Scheme, SecurityLevel},
+    plain_text::Plaintext,
+    serialization,
+};
+
+/// The magic bytes starting a saved key set
+const MAGIC: [u8; 4] = *b"SKEY";
+/// The version of the container format, bumped on incompatible changes
+const VERSION: u8 = 1;
+
+const HAS_PUBLIC_KEY: u8 = 0x01;
+const HAS_SECRET_KEY: u8 = 0x02;
+const HAS_RELINEARIZATION_KEYS: u8 = 0x04;
+const HAS_GALOIS_KEYS: u8 = 0x08;
+
+/// The parameters and the keys of a party, saved and loaded as one.
+///
+/// The container starts with a small header: the magic bytes `SKEY`, the
+/// format version, a byte flagging the keys present, the security level as
+/// a little endian `i32` and whether the modulus switching chain is
+/// expanded. The parameters follow, then the keys present in the order
+/// public, secret, relinearization, Galois, each saved by SEAL.
+///
+/// The keys are checked against the parameters, and against the secret key
+/// when it is present, with `validate`, which is also done when loading.
+/// Use `public_bundle` to hand the evaluation keys to an untrusted party
+/// without the secret key.
+pub struct KeySet {
+    context: Context,
+    public_key: Option<PublicKey>,
+    secret_key: Option<SecretKey>,
+    relinearization_keys: Option<RelinearizationKeys>,
+    galois_keys: Option<GaloisKeys>,
+}
+
+impl KeySet {
+    /// An empty key set for the parameters of the context
+    pub fn new(context: &Context) -> KeySet {
+        KeySet {
+            context: context.clone(),
+            public_key: None,
+            secret_key: None,
+            relinearization_keys: None,
+            galois_keys: None,
+        }
+    }
+
+    pub fn with_public_key(mut self, public_key: PublicKey) -> Self {
+        self.public_key = Some(public_key);
+        self
+    }
+
+    pub fn with_secret_key(mut self, secret_key: SecretKey) -> Self {
+        self.secret_key = Some(secret_key);
+        self
+    }
+
+    pub fn with_relinearization_keys(mut self, relinearization_keys: RelinearizationKeys) -> Self {
+        self.relinearization_keys = Some(relinearization_keys);
+        self
+    }
+
+    pub fn with_galois_keys(mut self, galois_keys: GaloisKeys) -> Self {
+        self.galois_keys = Some(galois_keys);
+        self
+    }
+
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// A copy of the encryption parameters of the key set
+    pub fn params(&self) -> Result<Params> {
+        self.context.parameters()
+    }
+
+    pub fn public_key(&self) -> Option<&PublicKey> {
+        self.public_key.as_ref()
+    }
+
+    pub fn secret_key(&self) -> Option<&SecretKey> {
+        self.secret_key.as_ref()
+    }
+
+    pub fn relinearization_keys(&self) -> Option<&RelinearizationKeys> {
+        self.relinearization_keys.as_ref()
+    }
+
+    pub fn galois_keys(&self) -> Option<&GaloisKeys> {
+        self.galois_keys.as_ref()
+    }
+
+    /// Check that every key present was generated for the parameters of the
+    /// key set and is well formed.
+    ///
+    /// When the secret key is present, the other keys are also checked
+    /// against it: a constant encrypted with the public key must decrypt,
+    /// and so must an encryption relinearized with the relinearization keys
+    /// and the encryptions transformed with each of the Galois keys. Keys
+    /// generated from another secret key for the same parameters are
+    /// rejected.
+    pub fn validate(&self) -> Result<()> {
+        let checks = [
+            (
+                self.public_key
+                    .as_ref()
+                    .map(|key| key.is_valid_for(&self.context)),
+                "public key",
+            ),
+            (
+                self.secret_key
+                    .as_ref()
+                    .map(|key| key.is_valid_for(&self.context)),
+                "secret key",
+            ),
+            (
+                self.relinearization_keys
+                    .as_ref()
+                    .map(|keys| keys.is_valid_for(&self.context)),
+                "relinearization keys",
+            ),
+            (
+                self.galois_keys
+                    .as_ref()
+                    .map(|keys| keys.is_valid_for(&self.context)),
+                "Galois keys",
+            ),
+        ];
+        for (valid, name) in checks {
+            if let Some(false) = valid.transpose()? {
+                return Err(SealError::invalid(
+                    "validating the key set",
+                    format!("invalid {} for the parameters", name),
+                ));
+            }
+        }
+        match &self.secret_key {
+            Some(secret_key) => self.validate_against(secret_key),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that the other keys were generated from the secret key
+    fn validate_against(&self, secret_key: &SecretKey) -> Result<()> {
+        let probe = Probe::create(&self.context, secret_key)?;
+        if let Some(public_key) = &self.public_key {
+            let encryptor = Encryptor::with_public_key(&self.context, public_key)?;
+            probe.check(&encryptor.encrypt(&probe.plain_text)?, "public key")?;
+        }
+        if self.relinearization_keys.is_none() && self.galois_keys.is_none() {
+            return Ok(());
+        }
+        let encryptor = Encryptor::with_secret_key(&self.context, secret_key)?;
+        let cipher_text = encryptor.encrypt(&probe.plain_text)?;
+        let evaluator = Evaluator::create(&self.context)?;
+        if let Some(relinearization_keys) = &self.relinearization_keys {
+            let squared = evaluator.square(&cipher_text)?;
+            probe.check(
+                &evaluator.relinearize(&squared, relinearization_keys)?,
+                "relinearization keys",
+            )?;
+        }
+        if let Some(galois_keys) = &self.galois_keys {
+            // loaded keys may have been put together from several key sets
+            for galois_elt in galois_keys.galois_elts()? {
+                probe.check(
+                    &evaluator.apply_galois(&cipher_text, galois_elt, galois_keys)?,
+                    "Galois keys",
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// A copy of the key set without the secret key: what a server needs to
+    /// evaluate on encrypted data, and nothing to decrypt it
+    pub fn public_bundle(&self) -> Result<KeySet> {
+        Ok(KeySet {
+            context: self.context.clone(),
+            public_key: self.public_key.as_ref().map(PublicKey::clone).transpose()?,
+            secret_key: None,
+            relinearization_keys: self
+                .relinearization_keys
+                .as_ref()
+                .map(RelinearizationKeys::clone)
+                .transpose()?,
+            galois_keys: self
+                .galois_keys
+                .as_ref()
+                .map(GaloisKeys::clone)
+                .transpose()?,
+        })
+    }
+
+    pub fn save(&self) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+        self.save_to(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Write the key set and return the number of bytes written
+    pub fn save_to<W: Write>(&self, mut writer: W) -> Result<usize> {
+        let mut flags = 0;
+        for (present, flag) in [
+            (self.public_key.is_some(), HAS_PUBLIC_KEY),
+            (self.secret_key.is_some(), HAS_SECRET_KEY),
+            (
+                self.relinearization_keys.is_some(),
+                HAS_RELINEARIZATION_KEYS,
+            ),
+            (self.galois_keys.is_some(), HAS_GALOIS_KEYS),
+        ] {
+            if present {
+                flags |= flag;
+            }
+        }
+        let mut header = MAGIC.to_vec();
+        header.push(VERSION);
+        header.push(flags);
+        header.extend_from_slice(&i32::from(self.context.security_level()).to_le_bytes());
+        header.push(self.context.expand_mod_chain() as u8);
+        let mut size = serialization::write(&mut writer, &header, "key set header")?;
+        size += self.params()?.save_to(&mut writer)?;
+        if let Some(key) = &self.public_key {
+            size += key.save_to(&mut writer)?;
+        }
+        if let Some(key) = &self.secret_key {
+            size += key.save_to(&mut writer)?;
+        }
+        if let Some(keys) = &self.relinearization_keys {
+            size += keys.save_to(&mut writer)?;
+        }
+        if let Some(keys) = &self.galois_keys {
+            size += keys.save_to(&mut writer)?;
+        }
+        Ok(size)
+    }
+
+    /// Load a key set saved by `save`, creating its context, and validate it
+    pub fn load(bytes: &[u8]) -> Result<KeySet> {
+        let mut reader = bytes;
+        let key_set = KeySet::load_from(&mut reader)?;
+        if !reader.is_empty() {
+            return Err(SealError::invalid(
+                "loading the key set",
+                format!("{} unexpected bytes after the keys", reader.len()),
+            ));
+        }
+        Ok(key_set)
+    }
+
+    /// Read a key set written by `save_to`, creating its context, and
+    /// validate it. Nothing is read past the key set.
+    pub fn load_from<R: Read>(mut reader: R) -> Result<KeySet> {
+        let mut header = [0u8; 11];
+        reader
+            .read_exact(&mut header)
+            .map_err(|err| SealError::stream("reading the key set header", err))?;
+        if header[..4] != MAGIC {
+            return Err(SealError::invalid(
+                "loading the key set",
+                "the data does not start with the key set magic bytes",
+            ));
+        }
+        if header[4] != VERSION {
+            return Err(SealError::invalid(
+                "loading the key set",
+                format!(
+                    "unsupported key set version {}, expected {}",
+                    header[4], VERSION
+                ),
+            ));
+        }
+        let flags = header[5];
+        if flags & !(HAS_PUBLIC_KEY | HAS_SECRET_KEY | HAS_RELINEARIZATION_KEYS | HAS_GALOIS_KEYS)
+            != 0
+        {
+            return Err(SealError::invalid(
+                "loading the key set",
+                format!("unknown key flags 0x{:02X}", flags),
+            ));
+        }
+        let security_level = SecurityLevel::try_from(i32::from_le_bytes([
+            header[6], header[7], header[8], header[9],
+        ]))?;
+        let expand_mod_chain = header[10] != 0;
+        let params = Params::load_from(&mut reader)?;
+        let context = Context::create(params, security_level, expand_mod_chain)?;
+        let mut key_set = KeySet::new(&context);
+        if flags & HAS_PUBLIC_KEY != 0 {
+            key_set.public_key = Some(PublicKey::load_from(&context, &mut reader)?);
+        }
+        if flags & HAS_SECRET_KEY != 0 {
+            key_set.secret_key = Some(SecretKey::load_from(&context, &mut reader)?);
+        }
+        if flags & HAS_RELINEARIZATION_KEYS != 0 {
+            key_set.relinearization_keys =
+                Some(RelinearizationKeys::load_from(&context, &mut reader)?);
+        }
+        if flags & HAS_GALOIS_KEYS != 0 {
+            key_set.galois_keys = Some(GaloisKeys::load_from(&context, &mut reader)?);
+        }
+        key_set.validate()?;
+        Ok(key_set)
+    }
+}
+
+/// An encryption of one, which squaring and the Galois automorphisms leave
+/// unchanged, checked by decrypting with the secret key
+struct Probe {
+    plain_text: Plaintext,
+    decryptor: Decryptor,
+    // the CKKS values are approximate
+    ckks_encoder: Option<CKKSEncoder>,
+}
+
+impl Probe {
+    fn create(context: &Context, secret_key: &SecretKey) -> Result<Probe> {
+        let decryptor = Decryptor::create(context, secret_key)?;
+        match context.parameters()?.scheme()? {
+            Scheme::Bfv | Scheme::Bgv => Ok(Probe {
+                plain_text: Plaintext::create_constant(1)?,
+                decryptor,
+                ckks_encoder: None,
+            }),
+            Scheme::Ckks => {
+                // small enough for the square to fit in the first level
+                let bit_count = context
+                    .first_context_data()?
+                    .total_coeff_modulus_bit_count()?;
+                let scale = 2f64.powi((bit_count.saturating_sub(10) / 2).min(40) as i32);
+                let mut ckks_encoder = CKKSEncoder::create(context)?;
+                Ok(Probe {
+                    plain_text: ckks_encoder.encode_value(&1.0, &scale)?,
+                    decryptor,
+                    ckks_encoder: Some(ckks_encoder),
+                })
+            }
+        }
+    }
+
+    /// Check that the cipher text decrypts to one, else the keys named were
+    /// not generated from the secret key
+    fn check(&self, cipher_text: &Ciphertext, name: &str) -> Result<()> {
+        let decrypted = self.decryptor.decrypt(cipher_text)?;
+        let matches = match &self.ckks_encoder {
+            Some(ckks_encoder) => ckks_encoder
+                .decode(&decrypted)?
+                .iter()
+                .all(|value| (value - 1.0).abs() < 0.5),
+            None => {
+                let coeffs = (0..decrypted.coeffs_count()?)
+                    .map(|index| decrypted.coeff_at(index))
+                    .collect::<Result<Vec<_>>>()?;
+                coeffs.first() == Some(&1) && coeffs[1..].iter().all(|&coeff| coeff == 0)
+            }
+        };
+        if matches {
+            Ok(())
+        } else {
+            Err(SealError::invalid(
+                "validating the key set",
+                format!("{} not generated from the secret key", name),
+            ))
+        }
+    }
+}
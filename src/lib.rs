@@ -16,13 +16,22 @@ mod batch_encoder;
 mod cipher_text;
 mod ckks_encoder;
 mod context;
+mod context_data;
 mod decryptor;
 mod encryptor;
+mod error;
 mod evaluator;
+mod expression;
 mod key_generator;
+mod key_set;
 mod memory_pool_handle;
 mod params;
+mod params_builder;
+mod params_planner;
+mod parms_id;
 mod plain_text;
+mod rotation_planner;
+mod serialization;
 mod small_modulus;
 
 #[cfg(test)]
@@ -32,17 +41,38 @@ pub type SmallModulus = small_modulus::SmallModulus;
 pub type Ciphertext = cipher_text::Ciphertext;
 pub type Plaintext = plain_text::Plaintext;
 pub type Params = params::Params;
+pub type ParamsBuilder = params_builder::ParamsBuilder;
+pub type ParamsPlanner = params_planner::ParamsPlanner;
+pub type PlanReport = params_planner::PlanReport;
+pub type ParmsId = parms_id::ParmsId;
+pub type RotationPlanner = rotation_planner::RotationPlanner;
+pub type RotationPlan = rotation_planner::RotationPlan;
+pub type Rotation = rotation_planner::Rotation;
 pub type MemoryPoolHandle = memory_pool_handle::MemoryPoolHandle;
 pub type KeyGenerator = key_generator::KeyGenerator;
 pub type PublicKey = key_generator::PublicKey;
 pub type SecretKey = key_generator::SecretKey;
 pub type RelinearizationKeys = key_generator::RelinearizationKeys;
 pub type GaloisKeys = key_generator::GaloisKeys;
+pub type KeySet = key_set::KeySet;
 pub type Evaluator = evaluator::Evaluator;
+pub type ExpressionEvaluator = expression::ExpressionEvaluator;
+pub type EncryptedValue = expression::EncryptedValue;
 pub type Encryptor = encryptor::Encryptor;
 pub type Decryptor = decryptor::Decryptor;
 pub type Context = context::Context;
+pub type ContextData<'a> = context_data::ContextData<'a>;
+pub type Qualifiers = context_data::Qualifiers;
 pub type BatchEncoder = batch_encoder::BatchEncoder;
 pub type CKKSEncoder = ckks_encoder::CKKSEncoder;
-pub const SCHEME_BFV: u8 = params::SCHEME_BFV;
-pub const SCHEME_CKKS: u8 = params::SCHEME_CKKS;
+pub type Scheme = params::Scheme;
+pub type SecurityLevel = params::SecurityLevel;
+pub type ComprMode = serialization::ComprMode;
+pub type SerializationHeader = serialization::SerializationHeader;
+pub type Serializable<T> = serialization::Serializable<T>;
+pub type SerializableCiphertext = serialization::Serializable<Ciphertext>;
+pub type SerializablePublicKey = serialization::Serializable<PublicKey>;
+pub type SerializableRelinearizationKeys = serialization::Serializable<RelinearizationKeys>;
+pub type SerializableGaloisKeys = serialization::Serializable<GaloisKeys>;
+pub type SealError = error::SealError;
+pub type Result<T, E = SealError> = error::Result<T, E>;
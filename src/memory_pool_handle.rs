@@ -1,10 +1,10 @@
 use std::os::raw::*;
 
-use anyhow::Result;
+use crate::{
+    error::{check_hresult, Result},
+    seal_bindings::*,
+};
 
-use crate::seal_bindings::*;
-
-#[derive(Clone)]
 pub struct MemoryPoolHandle {
     ptr: *mut ::std::os::raw::c_void,
 }
@@ -14,7 +14,16 @@ impl MemoryPoolHandle {
     pub fn to_thread_local_pool() -> Result<MemoryPoolHandle> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { MemoryManager_GetPool2(&mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the memory pool");
+        check_hresult!(ret, "creating the memory pool");
+        Ok(MemoryPoolHandle { ptr })
+    }
+
+    /// A new handle to the same pool. A derived `Clone` would copy the
+    /// pointer and destroy the handle twice
+    pub fn clone(&self) -> Result<MemoryPoolHandle> {
+        let mut ptr: *mut c_void = std::ptr::null_mut();
+        let ret = unsafe { MemoryPoolHandle_Create2(self.ptr, &mut ptr) };
+        check_hresult!(ret, "cloning the memory pool handle");
         Ok(MemoryPoolHandle { ptr })
     }
 
@@ -1,9 +1,88 @@
-use crate::{seal_bindings::*, SmallModulus};
-use anyhow::Result;
-use std::os::raw::*;
+use crate::{
+    error::{check_hresult, Result, SealError},
+    parms_id::ParmsId,
+    seal_bindings::*,
+    serialization::{self, ComprMode, SerializationHeader},
+    SmallModulus,
+};
+use std::{
+    io::{Read, Write},
+    os::raw::*,
+};
 
-pub const SCHEME_BFV: u8 = 0x01;
-pub const SCHEME_CKKS: u8 = 0x02;
+/// The homomorphic encryption schemes available in SEAL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    Bfv,
+    Ckks,
+    Bgv,
+}
+
+impl From<Scheme> for u8 {
+    fn from(scheme: Scheme) -> u8 {
+        match scheme {
+            Scheme::Bfv => 0x01,
+            Scheme::Ckks => 0x02,
+            Scheme::Bgv => 0x03,
+        }
+    }
+}
+
+impl TryFrom<u8> for Scheme {
+    type Error = SealError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x01 => Ok(Scheme::Bfv),
+            0x02 => Ok(Scheme::Ckks),
+            0x03 => Ok(Scheme::Bgv),
+            _ => Err(SealError::invalid(
+                "converting the scheme",
+                format!("unknown scheme: {}", value),
+            )),
+        }
+    }
+}
+
+/// The security levels of the HomomorphicEncryption.org standard, expressed
+/// in bits of classical security. `None` disables the check on the
+/// coefficient modulus size and must not be used in production.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SecurityLevel {
+    None,
+    #[default]
+    Tc128,
+    Tc192,
+    Tc256,
+}
+
+impl From<SecurityLevel> for i32 {
+    fn from(security_level: SecurityLevel) -> i32 {
+        match security_level {
+            SecurityLevel::None => 0,
+            SecurityLevel::Tc128 => 128,
+            SecurityLevel::Tc192 => 192,
+            SecurityLevel::Tc256 => 256,
+        }
+    }
+}
+
+impl TryFrom<i32> for SecurityLevel {
+    type Error = SealError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(SecurityLevel::None),
+            128 => Ok(SecurityLevel::Tc128),
+            192 => Ok(SecurityLevel::Tc192),
+            256 => Ok(SecurityLevel::Tc256),
+            _ => Err(SealError::invalid(
+                "converting the security level",
+                format!("unknown security level: {}", value),
+            )),
+        }
+    }
+}
 
 pub struct Params {
     ptr: *mut ::std::os::raw::c_void,
@@ -11,83 +90,86 @@ pub struct Params {
 
 #[allow(dead_code)]
 impl Params {
-    pub fn create(scheme: u8) -> Result<Params> {
+    pub fn create(scheme: Scheme) -> Result<Params> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { EncParams_Create1(scheme, &mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "failed creating the Params with scheme: {}",
-            scheme
-        );
+        let ret = unsafe { EncParams_Create1(scheme.into(), &mut ptr) };
+        check_hresult!(ret, "creating the Params with scheme: {:?}", scheme);
         // seal_bindings::Enc
         Ok(Params { ptr })
     }
 
+    /// Take ownership of params created by the C API
+    pub(crate) fn from_ptr(ptr: *mut c_void) -> Params {
+        Params { ptr }
+    }
+
     pub(crate) fn ptr(&self) -> *mut c_void {
         self.ptr
     }
 
+    /// The scheme these parameters were created for
+    pub fn scheme(&self) -> Result<Scheme> {
+        let mut scheme = 0u8;
+        let ret = unsafe { EncParams_GetScheme(self.ptr, &mut scheme) };
+        check_hresult!(ret, "getting the scheme");
+        Scheme::try_from(scheme)
+    }
+
     pub fn clone(&self) -> Result<Params> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { EncParams_Create2(self.ptr(), &mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error cloning the params: {}",
-            std::io::Error::last_os_error()
-        );
+        check_hresult!(ret, "cloning the params");
         Ok(Params { ptr })
     }
 
-    pub fn load(scheme: u8, bytes: &mut [u8]) -> Result<Params> {
+    /// Load the params from bytes. The scheme is read from the serialized
+    /// params and is available through `scheme()`
+    pub fn load(bytes: &mut [u8]) -> Result<Params> {
+        SerializationHeader::inspect(bytes)?;
         let mut ptr: *mut c_void = std::ptr::null_mut();
-        let ret = unsafe { EncParams_Create1(scheme, &mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "failed creating the Params with scheme: {}",
-            scheme
-        );
+        // the scheme is overwritten on load
+        let ret = unsafe { EncParams_Create1(0u8, &mut ptr) };
+        check_hresult!(ret, "creating the Params to load");
+        // dropped, and destroyed, if the loading fails
+        let params = Params { ptr };
         let mut uncompressed_size = 0i64;
-        let mut retry = 5;
-        while retry > 0 {
-            let ret = unsafe {
-                EncParams_Load(
-                    ptr,
-                    bytes.as_mut_ptr(),
-                    bytes.len() as u64,
-                    &mut uncompressed_size,
-                )
-            };
-            if ret == 0 {
-                return Ok(Params { ptr });
-            }
-            retry -= 1;
-        }
-        anyhow::bail!(
-            "Error loading the params: {}",
-            std::io::Error::last_os_error()
-        )
-    }
-
-    pub fn save(&self) -> Result<Vec<u8>> {
-        let compression_mode = 1u8; //bzip
-        let mut uncompressed_size: i64 = 0;
-        let ret = unsafe { EncParams_SaveSize(self.ptr, compression_mode, &mut uncompressed_size) };
-        anyhow::ensure!(ret == 0, "Error estimating the save size for the params");
-        let mut actual_size = 0i64;
-        let mut bytes: Vec<u8> = vec![0u8; uncompressed_size as usize];
         let ret = unsafe {
-            EncParams_Save(
-                self.ptr,
+            EncParams_Load(
+                params.ptr,
                 bytes.as_mut_ptr(),
-                uncompressed_size as u64,
-                compression_mode,
-                &mut actual_size,
+                bytes.len() as u64,
+                &mut uncompressed_size,
             )
         };
-        anyhow::ensure!(ret == 0, "Error saving the params");
-        // if compression is 'on', the actual size
-        // will be less than the uncompressed_size
-        Ok(bytes[0..actual_size as usize].to_vec())
+        check_hresult!(ret, "loading the params");
+        Ok(params)
+    }
+
+    pub fn save(&self) -> Result<Vec<u8>> {
+        self.save_with(ComprMode::default_mode()?)
+    }
+
+    pub fn save_with(&self, mode: ComprMode) -> Result<Vec<u8>> {
+        serialization::save(self.ptr, mode, EncParams_SaveSize, EncParams_Save, "params")
+    }
+
+    /// Write the object saved with the default compression mode and return
+    /// the number of bytes written. The C API only saves to memory: the
+    /// object is saved in a buffer of its worst case size, then written.
+    pub fn save_to<W: Write>(&self, writer: W) -> Result<usize> {
+        self.save_to_with(writer, ComprMode::default_mode()?)
+    }
+
+    /// `save_to` with the given compression mode, which must be supported
+    /// by this build
+    pub fn save_to_with<W: Write>(&self, writer: W, mode: ComprMode) -> Result<usize> {
+        serialization::write(writer, &self.save_with(mode)?, "params")
+    }
+
+    /// Load the params from a reader, without reading past them. The C API
+    /// only loads from memory: the params are read in a buffer first.
+    pub fn load_from<R: Read>(reader: R) -> Result<Params> {
+        Params::load(&mut serialization::read(reader, "params")?)
     }
 
     ///The first parameter we set is the degree of the `polynomial modulus'.
@@ -101,11 +183,7 @@ impl Params {
     /// 32768, but it is also possible to go beyond this range.
     pub fn set_poly_modulus_degree(&self, modulus_degree: usize) -> Result<()> {
         let ret = unsafe { EncParams_SetPolyModulusDegree(self.ptr, modulus_degree as u64) };
-        anyhow::ensure!(
-            ret == 0,
-            "failed setting modulus degree: {}",
-            modulus_degree
-        );
+        check_hresult!(ret, "setting modulus degree: {}", modulus_degree);
         Ok(())
     }
 
@@ -113,7 +191,7 @@ impl Params {
     pub fn get_poly_modulus_degree(&self) -> Result<usize> {
         let mut modulus_degree: u64 = 0;
         let ret = unsafe { EncParams_GetPolyModulusDegree(self.ptr, &mut modulus_degree) };
-        anyhow::ensure!(ret == 0, "failed getting modulus degree");
+        check_hresult!(ret, "getting modulus degree");
         Ok(modulus_degree as usize)
     }
 
@@ -154,10 +232,31 @@ impl Params {
         let ret = unsafe {
             EncParams_SetCoeffModulus(self.ptr, primes.len() as u64, coeffs.as_mut_ptr())
         };
-        anyhow::ensure!(ret == 0, "unable to set the coefficient modulus",);
+        check_hresult!(ret, "setting the coefficient modulus");
         Ok(())
     }
 
+    /// The largest bit-length of the coeff_modulus which achieves the
+    /// security level for the given poly_modulus_degree.
+    /// See the table in `set_coeff_modulus`
+    pub fn max_bit_count(poly_modulus_degree: usize, security_level: SecurityLevel) -> Result<u32> {
+        let mut bit_count = 0i32;
+        let ret = unsafe {
+            CoeffModulus_MaxBitCount(
+                poly_modulus_degree as u64,
+                security_level.into(),
+                &mut bit_count,
+            )
+        };
+        check_hresult!(
+            ret,
+            "getting the max bit count for degree {} and security: {:?}",
+            poly_modulus_degree,
+            security_level
+        );
+        Ok(bit_count as u32)
+    }
+
     ///    Microsoft SEAL comes with helper functions for selecting the
     /// coeff_modulus. For new users the easiest way is to simply use
     ///
@@ -165,25 +264,41 @@ impl Params {
     ///
     ///    which returns std::vector<SmallModulus> consisting of a generally
     /// good choice for the given poly_modulus_degree.
-    pub fn bfv_default(&self, security_level: u8) -> Result<Vec<SmallModulus>> {
+    ///
+    ///    The same defaults apply to the BGV scheme. CKKS parameters should
+    /// be set using `set_coeff_modulus_ckks` instead.
+    pub fn bfv_default(&self, security_level: SecurityLevel) -> Result<Vec<SmallModulus>> {
+        let scheme = self.scheme()?;
+        if scheme != Scheme::Bfv && scheme != Scheme::Bgv {
+            return Err(SealError::invalid(
+                "getting the default coefficients modulus",
+                format!(
+                    "the default coefficients modulus is only available for BFV and BGV, not \
+                     for scheme: {:?}",
+                    scheme
+                ),
+            ));
+        }
         let poly_modulus_degree = self.get_poly_modulus_degree()?;
-        anyhow::ensure!(
-            poly_modulus_degree != 0,
-            "set the polynomials modulus degree first"
-        );
+        if poly_modulus_degree == 0 {
+            return Err(SealError::invalid(
+                "getting the default coefficients modulus",
+                "set the polynomials modulus degree first",
+            ));
+        }
         let mut coeffs_length = 0u64;
         // first call to get the size
         let ret = unsafe {
             CoeffModulus_BFVDefault(
                 poly_modulus_degree as u64,
-                security_level as i32,
+                security_level.into(),
                 &mut coeffs_length,
                 std::ptr::null_mut(),
             )
         };
-        anyhow::ensure!(
-            ret == 0,
-            "unable to get the size in coefficients modulus init for security: {}",
+        check_hresult!(
+            ret,
+            "getting the size in coefficients modulus init for security: {:?}",
             security_level
         );
         // now get the coeffs
@@ -191,14 +306,14 @@ impl Params {
         let ret = unsafe {
             CoeffModulus_BFVDefault(
                 poly_modulus_degree as u64,
-                security_level as i32,
+                security_level.into(),
                 &mut coeffs_length,
                 coeffs.as_mut_ptr(),
             )
         };
-        anyhow::ensure!(
-            ret == 0,
-            "unable to get the default coefficients modulus for security: {}",
+        check_hresult!(
+            ret,
+            "getting the default coefficients modulus for security: {:?}",
             security_level
         );
         Ok(coeffs
@@ -209,10 +324,12 @@ impl Params {
 
     pub fn set_coeff_modulus_ckks(&self, bits_sizes: &mut [i32]) -> Result<()> {
         let poly_modulus_degree = self.get_poly_modulus_degree()?;
-        anyhow::ensure!(
-            poly_modulus_degree != 0,
-            "set the polynomials modulus degree first"
-        );
+        if poly_modulus_degree == 0 {
+            return Err(SealError::invalid(
+                "setting the coefficients modulus",
+                "set the polynomials modulus degree first",
+            ));
+        }
         let mut coeffs = vec![std::ptr::null_mut(); bits_sizes.len()];
         let ret = unsafe {
             CoeffModulus_Create1(
@@ -222,17 +339,17 @@ impl Params {
                 coeffs.as_mut_ptr(),
             )
         };
-        anyhow::ensure!(
-            ret == 0,
-            "unable to get the CKKS default coefficients modulus for coeffs: {:?}",
+        check_hresult!(
+            ret,
+            "getting the CKKS default coefficients modulus for coeffs: {:?}",
             &bits_sizes
         );
         let ret = unsafe {
             EncParams_SetCoeffModulus(self.ptr, bits_sizes.len() as u64, coeffs.as_mut_ptr())
         };
-        anyhow::ensure!(
-            ret == 0,
-            "unable to set the coefficients modulus for CKKS security: {:?}",
+        check_hresult!(
+            ret,
+            "setting the CKKS coefficient modulus with bit sizes: {:?}",
             bits_sizes
         );
         Ok(())
@@ -252,26 +369,43 @@ impl Params {
     /// and the noise budget consumption in a homomorphic multiplication is
     /// of the form log2(plain_modulus) + (other terms).
     ///
-    /// The plaintext modulus is specific to the BFV scheme, and cannot be
-    /// set when using the CKKS scheme.
+    /// The plaintext modulus is specific to the BFV and BGV schemes, and
+    /// cannot be set when using the CKKS scheme.
     pub fn set_plain_modulus(&self, plain_modulus: u64) -> Result<()> {
         let ret = unsafe { EncParams_SetPlainModulus2(self.ptr, plain_modulus) };
-        anyhow::ensure!(
-            ret == 0,
-            "unable to set the plain modulus to: {}",
-            plain_modulus
-        );
+        check_hresult!(ret, "setting the plain modulus to: {}", plain_modulus);
         Ok(())
     }
 
+    /// The primes of the coeff_modulus, see setter
+    pub fn get_coeff_modulus(&self) -> Result<Vec<u64>> {
+        let mut length: u64 = 0;
+        let ret = unsafe { EncParams_GetCoeffModulus(self.ptr, &mut length, std::ptr::null_mut()) };
+        check_hresult!(ret, "getting the coeff modulus length");
+        let mut primes = vec![std::ptr::null_mut(); length as usize];
+        let ret = unsafe { EncParams_GetCoeffModulus(self.ptr, &mut length, primes.as_mut_ptr()) };
+        check_hresult!(ret, "getting the coeff modulus");
+        // the C API returns copies of the primes which must be destroyed
+        let primes = primes
+            .into_iter()
+            .map(|ptr| SmallModulus { ptr })
+            .collect::<Vec<SmallModulus>>();
+        primes.iter().map(|prime| prime.value()).collect()
+    }
+
+    /// The id of these parameters, computed from all the values set
+    pub fn parms_id(&self) -> Result<ParmsId> {
+        ParmsId::read(self.ptr, EncParams_GetParmsId, "params")
+    }
+
     /// see setter
     pub fn get_plain_modulus(&self) -> Result<u64> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { EncParams_GetPlainModulus(self.ptr, &mut ptr) };
-        anyhow::ensure!(ret == 0, "failed getting the plain modulus");
+        check_hresult!(ret, "getting the plain modulus");
         let mut modulus_degree: u64 = 0;
         let ret = unsafe { Modulus_Value(ptr, &mut modulus_degree) };
-        anyhow::ensure!(ret == 0, "failed extracting the plain modulus");
+        check_hresult!(ret, "extracting the plain modulus");
         Ok(modulus_degree)
     }
 }
@@ -283,3 +417,7 @@ impl Drop for Params {
         }
     }
 }
+
+// The setters modify the params through `&self`: params can be moved to
+// another thread but not shared
+unsafe impl Send for Params {}
@@ -0,0 +1,220 @@
+use crate::{
+    context::Context,
+    error::{Result, SealError},
+    params::{Params, Scheme, SecurityLevel},
+    small_modulus::SmallModulus,
+};
+
+/// The largest prime size, in bits, accepted by SEAL
+const MAX_MODULUS_BIT_COUNT: i32 = 60;
+/// The smallest plain modulus size, in bits, accepted by SEAL
+const MIN_PLAIN_MODULUS_BIT_COUNT: u8 = 2;
+
+enum CoeffModulus {
+    Primes(Vec<u64>),
+    BitSizes(Vec<i32>),
+    Default,
+}
+
+enum PlainModulus {
+    Value(u64),
+    Batching(u8),
+}
+
+/// Build the encryption parameters and the `Context` in one call.
+///
+/// The setters can be called in any order: all the values are only checked
+/// and applied when calling `build()` or `build_params()`, and all the
+/// problems found are reported together.
+pub struct ParamsBuilder {
+    scheme: Scheme,
+    poly_modulus_degree: Option<usize>,
+    coeff_modulus: Option<CoeffModulus>,
+    plain_modulus: Option<PlainModulus>,
+    security_level: SecurityLevel,
+    expand_mod_chain: bool,
+}
+
+impl ParamsBuilder {
+    /// Start building parameters for the given scheme, with a 128 bits
+    /// security level and an expanded modulus switching chain
+    pub fn new(scheme: Scheme) -> ParamsBuilder {
+        ParamsBuilder {
+            scheme,
+            poly_modulus_degree: None,
+            coeff_modulus: None,
+            plain_modulus: None,
+            security_level: SecurityLevel::default(),
+            expand_mod_chain: true,
+        }
+    }
+
+    /// The degree of the polynomial modulus: a power of 2.
+    /// See `Params::set_poly_modulus_degree`
+    pub fn poly_modulus_degree(mut self, poly_modulus_degree: usize) -> Self {
+        self.poly_modulus_degree = Some(poly_modulus_degree);
+        self
+    }
+
+    /// Use the given primes as the coefficient modulus
+    pub fn coeff_modulus_primes(mut self, primes: &[u64]) -> Self {
+        self.coeff_modulus = Some(CoeffModulus::Primes(primes.to_vec()));
+        self
+    }
+
+    /// Let SEAL generate primes of the given bit sizes, suitable for the
+    /// polynomial modulus degree, as the coefficient modulus.
+    /// This is the usual way of setting the coefficient modulus for CKKS
+    pub fn coeff_modulus_bit_sizes(mut self, bit_sizes: &[i32]) -> Self {
+        self.coeff_modulus = Some(CoeffModulus::BitSizes(bit_sizes.to_vec()));
+        self
+    }
+
+    /// Use the SEAL default coefficient modulus for the polynomial modulus
+    /// degree and the security level. Only available for BFV and BGV.
+    /// See `Params::bfv_default`
+    pub fn coeff_modulus_default(mut self) -> Self {
+        self.coeff_modulus = Some(CoeffModulus::Default);
+        self
+    }
+
+    /// Use the given plain modulus (BFV and BGV only)
+    pub fn plain_modulus(mut self, plain_modulus: u64) -> Self {
+        self.plain_modulus = Some(PlainModulus::Value(plain_modulus));
+        self
+    }
+
+    /// Use a prime plain modulus of the given bit size which enables
+    /// batching (BFV and BGV only). See `SmallModulus::for_batching`
+    pub fn plain_modulus_batching(mut self, bit_size: u8) -> Self {
+        self.plain_modulus = Some(PlainModulus::Batching(bit_size));
+        self
+    }
+
+    /// The security level enforced by the context, and used to select the
+    /// default coefficient modulus
+    pub fn security_level(mut self, security_level: SecurityLevel) -> Self {
+        self.security_level = security_level;
+        self
+    }
+
+    /// Whether the context should create the modulus switching chain
+    pub fn expand_mod_chain(mut self, expand_mod_chain: bool) -> Self {
+        self.expand_mod_chain = expand_mod_chain;
+        self
+    }
+
+    /// List all the problems with the values set on the builder
+    fn errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        match self.poly_modulus_degree {
+            None => errors.push("the polynomial modulus degree is not set".to_string()),
+            Some(degree) if !degree.is_power_of_two() || degree < 2 => errors.push(format!(
+                "the polynomial modulus degree must be a power of 2, not {}",
+                degree
+            )),
+            _ => {}
+        }
+        match &self.coeff_modulus {
+            None => errors.push("the coefficient modulus is not set".to_string()),
+            Some(CoeffModulus::Primes(primes)) if primes.is_empty() => {
+                errors.push("the coefficient modulus primes are empty".to_string())
+            }
+            Some(CoeffModulus::BitSizes(bit_sizes)) if bit_sizes.is_empty() => {
+                errors.push("the coefficient modulus bit sizes are empty".to_string())
+            }
+            Some(CoeffModulus::BitSizes(bit_sizes)) => {
+                if let Some(bit_size) = bit_sizes
+                    .iter()
+                    .find(|&&bit_size| !(1..=MAX_MODULUS_BIT_COUNT).contains(&bit_size))
+                {
+                    errors.push(format!(
+                        "the coefficient modulus bit sizes must be between 1 and {}, not {}",
+                        MAX_MODULUS_BIT_COUNT, bit_size
+                    ));
+                }
+            }
+            Some(CoeffModulus::Default) if self.scheme == Scheme::Ckks => errors.push(
+                "the default coefficient modulus is not available for CKKS, set the bit sizes"
+                    .to_string(),
+            ),
+            _ => {}
+        }
+        match (&self.plain_modulus, self.scheme) {
+            (Some(_), Scheme::Ckks) => {
+                errors.push("the plain modulus cannot be set for CKKS".to_string())
+            }
+            (None, Scheme::Bfv | Scheme::Bgv) => errors.push(format!(
+                "the plain modulus is required for {:?}",
+                self.scheme
+            )),
+            (Some(PlainModulus::Batching(bit_size)), _)
+                if *bit_size < MIN_PLAIN_MODULUS_BIT_COUNT
+                    || *bit_size as i32 > MAX_MODULUS_BIT_COUNT =>
+            {
+                errors.push(format!(
+                    "the plain modulus bit size must be between {} and {}, not {}",
+                    MIN_PLAIN_MODULUS_BIT_COUNT, MAX_MODULUS_BIT_COUNT, bit_size
+                ))
+            }
+            _ => {}
+        }
+        errors
+    }
+
+    /// Check the values and create the `Params`
+    pub fn build_params(&self) -> Result<Params> {
+        let errors = self.errors();
+        if !errors.is_empty() {
+            return Err(SealError::invalid(
+                "building the parameters",
+                format!("Invalid parameters: {}", errors.join("; ")),
+            ));
+        }
+        // the checks above guarantee these are set
+        let (poly_modulus_degree, coeff_modulus) =
+            match (self.poly_modulus_degree, &self.coeff_modulus) {
+                (Some(degree), Some(coeff_modulus)) => (degree, coeff_modulus),
+                _ => {
+                    return Err(SealError::invalid(
+                        "building the parameters",
+                        "Invalid parameters",
+                    ))
+                }
+            };
+
+        let params = Params::create(self.scheme)?;
+        params.set_poly_modulus_degree(poly_modulus_degree)?;
+        match coeff_modulus {
+            CoeffModulus::Primes(primes) => {
+                let primes = primes
+                    .iter()
+                    .map(|&prime| SmallModulus::create(prime))
+                    .collect::<Result<Vec<SmallModulus>>>()?;
+                params.set_coeff_modulus(&primes)?;
+            }
+            CoeffModulus::BitSizes(bit_sizes) => {
+                params.set_coeff_modulus_ckks(&mut bit_sizes.clone())?;
+            }
+            CoeffModulus::Default => {
+                params.set_coeff_modulus(&params.bfv_default(self.security_level)?)?;
+            }
+        }
+        match self.plain_modulus {
+            Some(PlainModulus::Value(plain_modulus)) => params.set_plain_modulus(plain_modulus)?,
+            Some(PlainModulus::Batching(bit_size)) => params.set_plain_modulus(
+                SmallModulus::for_batching(poly_modulus_degree, bit_size)?.value()?,
+            )?,
+            None => {}
+        }
+        Ok(params)
+    }
+
+    /// Check the values, create the `Params` and the `Context`.
+    /// Errors found by SEAL while validating the parameters are reported
+    /// with their SEAL name and message
+    pub fn build(self) -> Result<Context> {
+        let params = self.build_params()?;
+        Context::create(params, self.security_level, self.expand_mod_chain)
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{
+    error::{Result, SealError},
+    params::{Params, Scheme, SecurityLevel},
+    small_modulus::SmallModulus,
+};
+
+/// The polynomial modulus degrees tried by the planner, smallest first
+const POLY_MODULUS_DEGREES: [usize; 6] = [1024, 2048, 4096, 8192, 16384, 32768];
+/// The largest prime size, in bits, accepted by SEAL
+const MAX_PRIME_BIT_COUNT: u32 = 60;
+/// Extra bits of noise consumed by a multiplication followed by a
+/// relinearization, on top of `log2(plain_modulus) + log2(degree)`
+const MUL_NOISE_MARGIN: u32 = 8;
+/// Noise budget kept in reserve after the last multiplication
+const NOISE_BUDGET_MARGIN: u32 = 10;
+
+/// What the planner found for a circuit, next to the `Params`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanReport {
+    pub poly_modulus_degree: usize,
+    /// The bit sizes of the coefficient modulus primes; the last one is the
+    /// special prime used for key switching
+    pub coeff_modulus_bit_sizes: Vec<i32>,
+    /// The maximum coefficient modulus bit count for the degree and the
+    /// security level
+    pub max_bit_count: u32,
+    /// BFV and BGV only: the batching friendly plain modulus
+    pub plain_modulus: Option<u64>,
+    /// BFV and BGV only: the estimated invariant noise budget, in bits, left
+    /// in a fresh cipher text after the planned number of multiplications
+    pub expected_noise_budget: Option<i32>,
+    /// The number of modulus switches (or rescales for CKKS) a fresh cipher
+    /// text can go through
+    pub levels: usize,
+}
+
+/// Select the encryption parameters for a circuit of a given multiplicative
+/// depth, instead of hand-tuning the degree and the coefficient modulus.
+///
+/// For BFV and BGV the noise growth is estimated with a conservative
+/// heuristic: a fresh cipher text has a noise budget of around
+/// `log2(q) - log2(t) - log2(n) / 2` bits and each relinearized
+/// multiplication consumes around `log2(t) + log2(n)` bits, plus margins.
+/// The actual budget should be checked with
+/// `Decryptor::invariant_noise_budget` on real data.
+///
+/// For CKKS the coefficient modulus is made of a first prime of
+/// `scale + precision` bits, one prime of `scale` bits per multiplication,
+/// and a special prime.
+pub struct ParamsPlanner {
+    scheme: Scheme,
+    depth: usize,
+    plain_bit_size: u8,
+    scale_bits: u32,
+    precision_bits: u32,
+    security_level: SecurityLevel,
+}
+
+impl ParamsPlanner {
+    /// Plan BFV parameters for `depth` multiplications of batched plain
+    /// texts of `plain_bit_size` bits
+    pub fn bfv(depth: usize, plain_bit_size: u8) -> ParamsPlanner {
+        ParamsPlanner::integer(Scheme::Bfv, depth, plain_bit_size)
+    }
+
+    /// Plan BGV parameters for `depth` multiplications of batched plain
+    /// texts of `plain_bit_size` bits. The coefficient modulus has at
+    /// least one prime per multiplication so that the cipher text can be
+    /// switched to the next modulus after each of them.
+    pub fn bgv(depth: usize, plain_bit_size: u8) -> ParamsPlanner {
+        ParamsPlanner::integer(Scheme::Bgv, depth, plain_bit_size)
+    }
+
+    /// Plan CKKS parameters for `depth` multiplications (and rescales) at a
+    /// scale of `2^scale_bits`, keeping `precision_bits` bits for the
+    /// integer part of the result
+    pub fn ckks(depth: usize, scale_bits: u32, precision_bits: u32) -> ParamsPlanner {
+        ParamsPlanner {
+            scheme: Scheme::Ckks,
+            depth,
+            plain_bit_size: 0,
+            scale_bits,
+            precision_bits,
+            security_level: SecurityLevel::default(),
+        }
+    }
+
+    fn integer(scheme: Scheme, depth: usize, plain_bit_size: u8) -> ParamsPlanner {
+        ParamsPlanner {
+            scheme,
+            depth,
+            plain_bit_size,
+            scale_bits: 0,
+            precision_bits: 0,
+            security_level: SecurityLevel::default(),
+        }
+    }
+
+    /// The security level to achieve, 128 bits by default
+    pub fn security_level(mut self, security_level: SecurityLevel) -> Self {
+        self.security_level = security_level;
+        self
+    }
+
+    /// Find the smallest polynomial modulus degree which fits the circuit
+    /// and return the matching `Params` with a report
+    pub fn plan(&self) -> Result<(Params, PlanReport)> {
+        match self.scheme {
+            Scheme::Ckks => self.plan_ckks(),
+            Scheme::Bfv | Scheme::Bgv => self.plan_integer(),
+        }
+    }
+
+    fn plan_integer(&self) -> Result<(Params, PlanReport)> {
+        let plain_bits = self.plain_bit_size as u32;
+        if !(2..=MAX_PRIME_BIT_COUNT).contains(&plain_bits) {
+            return Err(SealError::invalid(
+                "planning the parameters",
+                format!(
+                    "the plain modulus bit size must be between 2 and {}, not {}",
+                    MAX_PRIME_BIT_COUNT, plain_bits
+                ),
+            ));
+        }
+        for poly_modulus_degree in POLY_MODULUS_DEGREES {
+            let max_bit_count = Params::max_bit_count(poly_modulus_degree, self.security_level)?;
+            let log_degree = poly_modulus_degree.trailing_zeros();
+            let fresh_noise = log_degree / 2 + 1;
+            let mul_noise = plain_bits + log_degree + MUL_NOISE_MARGIN;
+            let needed_bits =
+                plain_bits + fresh_noise + self.depth as u32 * mul_noise + NOISE_BUDGET_MARGIN;
+            // spread the needed bits over as few primes as possible,
+            // BGV needs a prime to switch away after each multiplication
+            let mut prime_count = needed_bits.div_ceil(MAX_PRIME_BIT_COUNT);
+            if self.scheme == Scheme::Bgv {
+                prime_count = prime_count.max(self.depth as u32 + 1);
+            }
+            // primes must be congruent to 1 modulo 2 * degree
+            let prime_bits = needed_bits
+                .div_ceil(prime_count)
+                .max(log_degree + 2)
+                .min(MAX_PRIME_BIT_COUNT);
+            // the special prime is as large as the largest data prime
+            if prime_bits * (prime_count + 1) > max_bit_count {
+                continue;
+            }
+            let plain_modulus =
+                SmallModulus::for_batching(poly_modulus_degree, self.plain_bit_size)
+                    .and_then(|modulus| modulus.value())
+                    .map_err(|err| {
+                        SealError::invalid(
+                            "planning the parameters",
+                            format!(
+                                "no batching plain modulus of {} bits for degree {}: {}",
+                                plain_bits, poly_modulus_degree, err
+                            ),
+                        )
+                    })?;
+            let mut bit_sizes = vec![prime_bits as i32; prime_count as usize + 1];
+            let params = Params::create(self.scheme)?;
+            params.set_poly_modulus_degree(poly_modulus_degree)?;
+            params.set_coeff_modulus_ckks(&mut bit_sizes)?;
+            params.set_plain_modulus(plain_modulus)?;
+            let data_bits = prime_bits * prime_count;
+            let expected_noise_budget = data_bits as i32
+                - plain_bits as i32
+                - fresh_noise as i32
+                - (self.depth as u32 * mul_noise) as i32;
+            return Ok((
+                params,
+                PlanReport {
+                    poly_modulus_degree,
+                    coeff_modulus_bit_sizes: bit_sizes,
+                    max_bit_count,
+                    plain_modulus: Some(plain_modulus),
+                    expected_noise_budget: Some(expected_noise_budget),
+                    levels: prime_count as usize - 1,
+                },
+            ));
+        }
+        Err(SealError::invalid(
+            "planning the parameters",
+            format!(
+                "no polynomial modulus degree supports {} multiplications of {} bits plain \
+                 texts for security: {:?}",
+                self.depth, plain_bits, self.security_level
+            ),
+        ))
+    }
+
+    fn plan_ckks(&self) -> Result<(Params, PlanReport)> {
+        let first_bits = self.scale_bits + self.precision_bits;
+        if first_bits > MAX_PRIME_BIT_COUNT {
+            return Err(SealError::invalid(
+                "planning the parameters",
+                format!(
+                    "the scale and the precision must fit in {} bits, not {}",
+                    MAX_PRIME_BIT_COUNT, first_bits
+                ),
+            ));
+        }
+        for poly_modulus_degree in POLY_MODULUS_DEGREES {
+            let max_bit_count = Params::max_bit_count(poly_modulus_degree, self.security_level)?;
+            // primes must be congruent to 1 modulo 2 * degree
+            if self.scale_bits < poly_modulus_degree.trailing_zeros() + 2 {
+                continue;
+            }
+            let total_bits = 2 * first_bits + self.depth as u32 * self.scale_bits;
+            if total_bits > max_bit_count {
+                continue;
+            }
+            let mut bit_sizes = vec![self.scale_bits as i32; self.depth + 2];
+            bit_sizes[0] = first_bits as i32;
+            bit_sizes[self.depth + 1] = first_bits as i32;
+            let params = Params::create(Scheme::Ckks)?;
+            params.set_poly_modulus_degree(poly_modulus_degree)?;
+            params.set_coeff_modulus_ckks(&mut bit_sizes)?;
+            return Ok((
+                params,
+                PlanReport {
+                    poly_modulus_degree,
+                    coeff_modulus_bit_sizes: bit_sizes,
+                    max_bit_count,
+                    plain_modulus: None,
+                    expected_noise_budget: None,
+                    levels: self.depth,
+                },
+            ));
+        }
+        Err(SealError::invalid(
+            "planning the parameters",
+            format!(
+                "no polynomial modulus degree supports {} multiplications at scale 2^{} for \
+                 security: {:?}",
+                self.depth, self.scale_bits, self.security_level
+            ),
+        ))
+    }
+}
@@ -0,0 +1,66 @@
+use std::{fmt, os::raw::*};
+
+use crate::error::{check_hresult, Result};
+
+/// The id of the encryption parameters of a level of the modulus switching
+/// chain. Cipher texts, CKKS plain texts and keys carry the id of the level
+/// they belong to, and operands must be at the same level.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ParmsId(pub [u64; 4]);
+
+impl ParmsId {
+    /// The id of the objects which are not bound to a level, such as BFV
+    /// and BGV plain texts
+    pub const ZERO: ParmsId = ParmsId([0; 4]);
+
+    pub fn is_zero(&self) -> bool {
+        *self == ParmsId::ZERO
+    }
+
+    /// Read the parms id of an object with one of the `*ParmsId` functions of
+    /// the C API
+    pub(crate) fn read(
+        ptr: *mut c_void,
+        f: unsafe extern "C" fn(*mut c_void, *mut u64) -> c_long,
+        name: &str,
+    ) -> Result<ParmsId> {
+        let mut parms_id = ParmsId::ZERO;
+        let ret = unsafe { f(ptr, parms_id.as_mut_ptr()) };
+        check_hresult!(ret, "getting the parms id of the {}", name);
+        Ok(parms_id)
+    }
+
+    /// The C API takes mutable pointers, even when the id is only read
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut u64 {
+        self.0.as_mut_ptr()
+    }
+}
+
+impl From<[u64; 4]> for ParmsId {
+    fn from(words: [u64; 4]) -> Self {
+        ParmsId(words)
+    }
+}
+
+impl From<ParmsId> for [u64; 4] {
+    fn from(parms_id: ParmsId) -> Self {
+        parms_id.0
+    }
+}
+
+/// Same format as SEAL: the 4 words in hexadecimal, separated by spaces
+impl fmt::Display for ParmsId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:016x} {:016x} {:016x} {:016x}",
+            self.0[0], self.0[1], self.0[2], self.0[3]
+        )
+    }
+}
+
+impl fmt::Debug for ParmsId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ParmsId({})", self)
+    }
+}
@@ -1,9 +1,16 @@
 use crate::{
-    cipher_text::Ciphertext, context::Context, memory_pool_handle::MemoryPoolHandle,
+    cipher_text::Ciphertext,
+    context::Context,
+    error::{check_hresult, Result},
+    memory_pool_handle::MemoryPoolHandle,
+    parms_id::ParmsId,
     seal_bindings::*,
+    serialization::{self, ComprMode, LoadFn},
+};
+use std::{
+    io::{Read, Write},
+    os::raw::*,
 };
-use anyhow::Result;
-use std::os::raw::*;
 
 pub struct Plaintext {
     ptr: *mut c_void,
@@ -21,12 +28,12 @@ impl Plaintext {
     pub fn create() -> Result<Plaintext> {
         let mut handle_ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { MemoryManager_GetPool2(&mut handle_ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the memory pool handle");
+        check_hresult!(ret, "creating the memory pool handle");
         let mut ptr: *mut c_void = std::ptr::null_mut();
         // the memory pool pointer is dereferenced to a MemoryPoolHandle
         // and copied
         let ret = unsafe { Plaintext_Create1(handle_ptr, &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the plain text");
+        check_hresult!(ret, "creating the plain text");
         Ok(Plaintext { ptr })
     }
 
@@ -35,7 +42,7 @@ impl Plaintext {
         // the memory pool pointer is dereferenced to a MemoryPoolHandle
         // and copied
         let ret = unsafe { Plaintext_Create1(memory_pool.ptr(), &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the plain text");
+        check_hresult!(ret, "creating the plain text");
         Ok(Plaintext { ptr })
     }
 
@@ -44,9 +51,9 @@ impl Plaintext {
     pub fn create_constant(value: u64) -> Result<Plaintext> {
         let pt = Plaintext::create()?;
         let ret = unsafe { Plaintext_Set3(pt.ptr(), value) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error creating the constant plain text with value: {}",
+        check_hresult!(
+            ret,
+            "creating the constant plain text with value: {}",
             value
         );
         Ok(pt)
@@ -58,10 +65,10 @@ impl Plaintext {
         // this call creates a new object which is
         // managed through a unique_pt in the create call
         let ret = unsafe { Plaintext_Pool(other.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the plain text memory pool");
+        check_hresult!(ret, "fetching the plain text memory pool");
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Plaintext_Create1(mem_pool_ptr, &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the plain text");
+        check_hresult!(ret, "creating the plain text");
         Ok(Plaintext { ptr })
     }
 
@@ -70,24 +77,24 @@ impl Plaintext {
         // this call creates a new object which is
         // managed through a unique_pt in the create call
         let ret = unsafe { Ciphertext_Pool(other.ptr(), &mut mem_pool_ptr) };
-        anyhow::ensure!(ret == 0, "Error fetching the cipher text memory pool");
+        check_hresult!(ret, "fetching the cipher text memory pool");
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Plaintext_Create1(mem_pool_ptr, &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the plain text");
+        check_hresult!(ret, "creating the plain text");
         Ok(Plaintext { ptr })
     }
 
     pub fn coeff_at(&self, index: usize) -> Result<u64> {
         let mut value: u64 = 0;
         let ret = unsafe { Plaintext_CoeffAt(self.ptr(), index as u64, &mut value) };
-        anyhow::ensure!(ret == 0, "Error getting the coefficient at: {}", index);
+        check_hresult!(ret, "getting the coefficient at: {}", index);
         Ok(value)
     }
 
     pub fn coeffs_count(&self) -> Result<usize> {
         let mut value: u64 = 0;
         let ret = unsafe { Plaintext_CoeffCount(self.ptr(), &mut value) };
-        anyhow::ensure!(ret == 0, "Error getting the coefficients count");
+        check_hresult!(ret, "getting the coefficients count");
         Ok(value as usize)
     }
 
@@ -95,39 +102,62 @@ impl Plaintext {
         self.ptr
     }
 
-    pub fn make_constant(&self, value: u64) -> Result<()> {
+    /// The scale of a CKKS plain text
+    pub fn scale(&self) -> Result<f64> {
+        let mut scale: f64 = 0.0;
+        let ret = unsafe { Plaintext_Scale(self.ptr, &mut scale) };
+        check_hresult!(ret, "getting the scale");
+        Ok(scale)
+    }
+
+    /// The id of the level of the modulus switching chain of a CKKS plain
+    /// text. BFV and BGV plain texts are not bound to a level and have the
+    /// `ParmsId::ZERO` id
+    pub fn parms_id(&self) -> Result<ParmsId> {
+        ParmsId::read(self.ptr, Plaintext_GetParmsId, "plain text")
+    }
+
+    pub fn make_constant(&mut self, value: u64) -> Result<()> {
         let ret = unsafe { Plaintext_Set3(self.ptr(), value) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error making the plain text as a constant with value: {}",
+        check_hresult!(
+            ret,
+            "making the plain text as a constant with value: {}",
             value
         );
         Ok(())
     }
 
     pub fn save(&self) -> Result<Vec<u8>> {
-        let compression_mode = 1u8; //bzip
-        let mut uncompressed_size: i64 = 0;
-        let ret = unsafe { Plaintext_SaveSize(self.ptr, compression_mode, &mut uncompressed_size) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error estimating the save size for the plain text"
-        );
-        let mut actual_size = 0i64;
-        let mut bytes: Vec<u8> = vec![0u8; uncompressed_size as usize];
-        let ret = unsafe {
-            Plaintext_Save(
-                self.ptr,
-                bytes.as_mut_ptr(),
-                uncompressed_size as u64,
-                compression_mode,
-                &mut actual_size,
-            )
-        };
-        anyhow::ensure!(ret == 0, "Error saving the plain text");
-        // if compression is 'on', the actual size
-        // will be less than the uncompressed_size
-        Ok(bytes[0..actual_size as usize].to_vec())
+        self.save_with(ComprMode::default_mode()?)
+    }
+
+    pub fn save_with(&self, mode: ComprMode) -> Result<Vec<u8>> {
+        serialization::save(
+            self.ptr,
+            mode,
+            Plaintext_SaveSize,
+            Plaintext_Save,
+            "plain text",
+        )
+    }
+
+    /// Write the object saved with the default compression mode and return
+    /// the number of bytes written. The C API only saves to memory: the
+    /// object is saved in a buffer of its worst case size, then written.
+    pub fn save_to<W: Write>(&self, writer: W) -> Result<usize> {
+        self.save_to_with(writer, ComprMode::default_mode()?)
+    }
+
+    /// `save_to` with the given compression mode, which must be supported
+    /// by this build
+    pub fn save_to_with<W: Write>(&self, writer: W, mode: ComprMode) -> Result<usize> {
+        serialization::write(writer, &self.save_with(mode)?, "plain text")
+    }
+
+    /// Read an object written by `save_to`, without reading past it. The C
+    /// API only loads from memory: the object is read in a buffer first.
+    pub fn load_from<R: Read>(context: &Context, reader: R) -> Result<Plaintext> {
+        Plaintext::load(context, &mut serialization::read(reader, "plain text")?)
     }
 
     /// load the plain text from compressed bytes
@@ -141,40 +171,60 @@ impl Plaintext {
         context: &Context,
         pool_handle: MemoryPoolHandle,
         bytes: &mut [u8],
+    ) -> Result<Plaintext> {
+        Plaintext::load_with(context, pool_handle, bytes, Plaintext_Load)
+    }
+
+    /// Load the plain text without checking it against the parameters.
+    ///
+    /// # Safety
+    ///
+    /// SEAL trusts the loaded data: the bytes must come from a trusted
+    /// source, or the plain text must be checked with `is_valid_for`
+    /// before use, since SEAL reads out of bounds on malformed data.
+    pub unsafe fn unsafe_load(context: &Context, bytes: &mut [u8]) -> Result<Plaintext> {
+        let pool_handle = MemoryPoolHandle::to_thread_local_pool()?;
+        Plaintext::load_with(context, pool_handle, bytes, Plaintext_UnsafeLoad)
+    }
+
+    fn load_with(
+        context: &Context,
+        pool_handle: MemoryPoolHandle,
+        bytes: &mut [u8],
+        load: LoadFn,
     ) -> Result<Plaintext> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Plaintext_Create1(pool_handle.ptr(), &mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error instantiating plain text: {}",
-            std::io::Error::last_os_error()
-        );
+        check_hresult!(ret, "instantiating plain text");
+        let loaded = Plaintext { ptr };
         let mut _actual_size: i64 = 0;
         let ret = unsafe {
-            Plaintext_Load(
-                ptr,
+            load(
+                loaded.ptr,
                 context.ptr(),
                 bytes.as_mut_ptr(),
                 bytes.len() as u64,
                 &mut _actual_size,
             )
         };
-        anyhow::ensure!(
-            ret == 0,
-            "Error loading the plain text: {}",
-            std::io::Error::last_os_error()
-        );
-        Ok(Plaintext { ptr })
+        check_hresult!(ret, "loading the plain text");
+        Ok(loaded)
+    }
+
+    /// Whether the plain text is valid for the parameters of the context
+    pub fn is_valid_for(&self, context: &Context) -> Result<bool> {
+        serialization::is_valid_for(
+            self.ptr,
+            context,
+            ValCheck_Plaintext_IsValidFor,
+            "plain text",
+        )
     }
 
     pub fn clone(&self) -> Result<Plaintext> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Plaintext_Create5(self.ptr(), &mut ptr) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error cloning the plain text: {}",
-            std::io::Error::last_os_error()
-        );
+        check_hresult!(ret, "cloning the plain text");
         Ok(Plaintext { ptr })
     }
 }
@@ -211,3 +261,7 @@ impl Drop for Plaintext {
         }
     }
 }
+
+// Plain texts are only modified through `&mut self`
+unsafe impl Send for Plaintext {}
+unsafe impl Sync for Plaintext {}
@@ -0,0 +1,147 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    cipher_text::Ciphertext,
+    context::Context,
+    encryptor::Encryptor,
+    error::Result,
+    evaluator::Evaluator,
+    key_generator::{GaloisKeys, KeyGenerator, RelinearizationKeys},
+};
+
+/// A rotation needing a Galois key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rotation {
+    /// The rotation of the vector (CKKS) or of the rows of the batching
+    /// matrix (BFV, BGV) by a number of slots, to the left when positive
+    Steps(i32),
+    /// The swap of the rows of the batching matrix (BFV, BGV) or the
+    /// complex conjugation (CKKS)
+    Conjugation,
+}
+
+/// The rotations of a computation and the Galois elements of the keys
+/// needed to perform them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationPlan {
+    /// The distinct rotations requested, sorted
+    pub rotations: Vec<Rotation>,
+    /// The Galois elements to generate keys for, sorted
+    pub galois_elts: Vec<u32>,
+}
+
+impl RotationPlan {
+    /// Generate the Galois keys of the plan, and only them
+    pub fn galois_keys(&self, key_generator: &KeyGenerator) -> Result<GaloisKeys> {
+        key_generator.galois_keys_for_elements(&self.galois_elts)
+    }
+}
+
+/// Find the Galois keys a computation needs by running it once on
+/// encryptions of zero with a recording `Evaluator`: its rotations are
+/// recorded and return a copy of their input, the other operations are
+/// performed.
+///
+/// With `naf`, each rotation is decomposed in its non-adjacent form, a sum
+/// of signed powers of 2, as SEAL does when the key of a rotation is
+/// missing. The keys are then shared between the rotations: there are fewer
+/// keys but each rotation may take several key switches.
+pub struct RotationPlanner {
+    context: Context,
+    naf: bool,
+}
+
+impl RotationPlanner {
+    pub fn new(context: &Context) -> RotationPlanner {
+        RotationPlanner {
+            context: context.clone(),
+            naf: false,
+        }
+    }
+
+    /// Decompose the rotations in their non-adjacent form, off by default
+    pub fn naf(mut self, naf: bool) -> Self {
+        self.naf = naf;
+        self
+    }
+
+    /// Run the computation on `input_count` encryptions of zero at the
+    /// first level and plan its rotations. The relinearization keys are
+    /// real, the Galois keys are empty.
+    pub fn plan<F, R>(&self, input_count: usize, computation: F) -> Result<RotationPlan>
+    where
+        F: FnOnce(&Evaluator, &[Ciphertext], &RelinearizationKeys, &GaloisKeys) -> Result<R>,
+    {
+        let key_generator = KeyGenerator::create(&self.context)?;
+        let relinearization_keys = key_generator.relinearization_keys()?;
+        let encryptor = Encryptor::with_public_key(&self.context, &key_generator.public_key()?)?;
+        let inputs = (0..input_count)
+            .map(|_| encryptor.encrypt_zero(None))
+            .collect::<Result<Vec<_>>>()?;
+        let evaluator = Evaluator::recording(&self.context)?;
+        computation(
+            &evaluator,
+            &inputs,
+            &relinearization_keys,
+            &GaloisKeys::empty()?,
+        )?;
+        self.plan_rotations(&evaluator.recorded_rotations())
+    }
+
+    /// Plan the keys of a known list of rotations. Rotations by 0 steps
+    /// need no key and are left out.
+    pub fn plan_rotations(&self, rotations: &[Rotation]) -> Result<RotationPlan> {
+        let half_degree = self
+            .context
+            .key_context_data()?
+            .parms()?
+            .get_poly_modulus_degree()? as i32
+            / 2;
+        let rotations: BTreeSet<Rotation> = rotations
+            .iter()
+            .copied()
+            .filter(|rotation| *rotation != Rotation::Steps(0))
+            .collect();
+        let mut galois_elts = BTreeSet::new();
+        for &rotation in &rotations {
+            match rotation {
+                Rotation::Steps(step) if self.naf => {
+                    // a rotation by a whole row is the identity
+                    for term in naf(step).into_iter().filter(|t| t.abs() != half_degree) {
+                        galois_elts.insert(self.context.galois_elt_from_step(term)?);
+                    }
+                }
+                Rotation::Steps(step) => {
+                    galois_elts.insert(self.context.galois_elt_from_step(step)?);
+                }
+                // the element SEAL computes for a step of 0
+                Rotation::Conjugation => {
+                    galois_elts.insert(self.context.galois_elt_from_step(0)?);
+                }
+            }
+        }
+        Ok(RotationPlan {
+            rotations: rotations.into_iter().collect(),
+            galois_elts: galois_elts.into_iter().collect(),
+        })
+    }
+}
+
+/// The non-adjacent form of a value: the signed powers of 2 adding up to
+/// it, no two of them adjacent, as computed by SEAL
+fn naf(value: i32) -> Vec<i32> {
+    let negative = value < 0;
+    let mut value = value.unsigned_abs() as i64;
+    let mut terms = Vec::new();
+    let mut power = 1i64;
+    while value != 0 {
+        let digit = if value & 1 == 1 { 2 - (value & 3) } else { 0 };
+        value = (value - digit) >> 1;
+        if digit != 0 {
+            let term = (digit * power) as i32;
+            terms.push(if negative { -term } else { term });
+        }
+        power <<= 1;
+    }
+    terms
+}
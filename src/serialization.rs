@@ -0,0 +1,337 @@
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    os::raw::*,
+};
+
+use crate::{
+    cipher_text::Ciphertext,
+    context::Context,
+    error::{check_hresult, Result, SealError},
+    key_generator::{GaloisKeys, PublicKey, RelinearizationKeys},
+    seal_bindings::*,
+};
+
+/// The compression applied by SEAL to the serialized objects.
+///
+/// The modes available depend on the `zlib` and `zstd` features this crate
+/// was built with; check them with `is_supported`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComprMode {
+    None,
+    Zlib,
+    Zstd,
+}
+
+impl From<ComprMode> for u8 {
+    fn from(mode: ComprMode) -> u8 {
+        match mode {
+            ComprMode::None => 0x00,
+            ComprMode::Zlib => 0x01,
+            ComprMode::Zstd => 0x02,
+        }
+    }
+}
+
+impl TryFrom<u8> for ComprMode {
+    type Error = SealError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(ComprMode::None),
+            0x01 => Ok(ComprMode::Zlib),
+            0x02 => Ok(ComprMode::Zstd),
+            _ => Err(SealError::invalid(
+                "converting the compression mode",
+                format!("unknown compression mode: {}", value),
+            )),
+        }
+    }
+}
+
+impl ComprMode {
+    /// The mode used by `save`: Zstd if available, then Zlib, then None
+    pub fn default_mode() -> Result<ComprMode> {
+        let mut mode = 0u8;
+        let ret = unsafe { Serialization_ComprModeDefault(&mut mode) };
+        check_hresult!(ret, "getting the default compression mode");
+        ComprMode::try_from(mode)
+    }
+
+    /// Whether SEAL was built with this compression mode
+    pub fn is_supported(&self) -> Result<bool> {
+        let mut supported = 0;
+        let ret = unsafe { Serialization_IsSupportedComprMode((*self).into(), &mut supported) };
+        check_hresult!(ret, "checking the compression mode: {:?}", self);
+        Ok(supported != 0)
+    }
+}
+
+/// The header SEAL writes in front of every serialized object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializationHeader {
+    pub magic: u16,
+    pub header_size: u8,
+    pub version_major: u8,
+    pub version_minor: u8,
+    pub compr_mode: ComprMode,
+    /// The size of the serialized object, header included
+    pub size: u64,
+}
+
+impl SerializationHeader {
+    /// The magic number starting the SEAL headers
+    pub fn seal_magic() -> Result<u16> {
+        let mut magic = 0u16;
+        let ret = unsafe { Serialization_SEALMagic(&mut magic) };
+        check_hresult!(ret, "getting the SEAL magic number");
+        Ok(magic)
+    }
+
+    /// The size of the SEAL headers, in bytes
+    pub fn seal_header_size() -> Result<usize> {
+        let mut header_size = 0u8;
+        let ret = unsafe { Serialization_SEALHeaderSize(&mut header_size) };
+        check_hresult!(ret, "getting the SEAL header size");
+        Ok(header_size as usize)
+    }
+
+    /// Parse and check the header at the start of serialized bytes, before
+    /// loading them. This fails if the bytes do not start with a SEAL
+    /// header, if they were saved by an incompatible version of SEAL or with
+    /// a compression mode which is not supported by this build.
+    ///
+    /// Only the header is required: the total size of the object is
+    /// available in `size` to check the rest of the bytes.
+    pub fn inspect(bytes: &[u8]) -> Result<SerializationHeader> {
+        let header_size = SerializationHeader::seal_header_size()?;
+        if bytes.len() < header_size {
+            return Err(invalid_header(format!(
+                "{} bytes is too short, the header has {}",
+                bytes.len(),
+                header_size
+            )));
+        }
+        let mut header_bytes = bytes[..header_size].to_vec();
+        let header = SerializationHeader {
+            magic: u16::from_le_bytes([header_bytes[0], header_bytes[1]]),
+            header_size: header_bytes[2],
+            version_major: header_bytes[3],
+            version_minor: header_bytes[4],
+            // checked below
+            compr_mode: ComprMode::None,
+            size: u64::from_le_bytes(header_bytes[8..16].try_into().unwrap_or_default()),
+        };
+        let seal_magic = SerializationHeader::seal_magic()?;
+        if header.magic != seal_magic {
+            return Err(invalid_header(format!(
+                "the magic number is 0x{:04X} instead of 0x{:04X}",
+                header.magic, seal_magic
+            )));
+        }
+        if header.header_size as usize != header_size {
+            return Err(invalid_header(format!(
+                "the header size is {} instead of {}",
+                header.header_size, header_size
+            )));
+        }
+        let mut compatible = 0;
+        let ret = unsafe {
+            Serialization_IsCompatibleVersion(
+                header_bytes.as_mut_ptr(),
+                header_size as u64,
+                &mut compatible,
+            )
+        };
+        check_hresult!(ret, "checking the SEAL version of the header");
+        if compatible == 0 {
+            return Err(invalid_header(format!(
+                "the version {}.{} of SEAL is not compatible",
+                header.version_major, header.version_minor
+            )));
+        }
+        let compr_mode = ComprMode::try_from(header_bytes[5])
+            .map_err(|_| invalid_header(format!("unknown compression mode {}", header_bytes[5])))?;
+        if !compr_mode.is_supported()? {
+            return Err(invalid_header(format!(
+                "the compression mode {:?} is not supported by this build",
+                compr_mode
+            )));
+        }
+        if header.size < header_size as u64 {
+            return Err(invalid_header(format!(
+                "the object size {} is smaller than the header",
+                header.size
+            )));
+        }
+        // the checks of SEAL itself, in case they get stricter
+        let mut valid = 0;
+        let ret = unsafe {
+            Serialization_IsValidHeader(header_bytes.as_mut_ptr(), header_size as u64, &mut valid)
+        };
+        check_hresult!(ret, "checking the header");
+        if valid == 0 {
+            return Err(invalid_header("rejected by SEAL"));
+        }
+        Ok(SerializationHeader {
+            compr_mode,
+            ..header
+        })
+    }
+}
+
+fn invalid_header(reason: impl fmt::Display) -> SealError {
+    SealError::invalid(
+        "inspecting the serialized data",
+        format!("invalid SEAL header: {}", reason),
+    )
+}
+
+/// An object generated with a seed: the second half of its data is replaced
+/// by the seed of the random generator which produced it, which halves the
+/// size of its serialization.
+///
+/// It can only be saved: loading the saved bytes, e.g. with
+/// `Ciphertext::load`, expands the seed into a regular object.
+pub struct Serializable<T> {
+    object: T,
+}
+
+impl<T> Serializable<T> {
+    pub(crate) fn new(object: T) -> Serializable<T> {
+        Serializable { object }
+    }
+}
+
+macro_rules! impl_serializable {
+    ($($t:ty),+) => {
+        $(
+            impl Serializable<$t> {
+                pub fn save(&self) -> Result<Vec<u8>> {
+                    self.object.save()
+                }
+
+                pub fn save_with(&self, mode: ComprMode) -> Result<Vec<u8>> {
+                    self.object.save_with(mode)
+                }
+
+                pub fn save_to<W: Write>(&self, writer: W) -> Result<usize> {
+                    self.object.save_to(writer)
+                }
+
+                pub fn save_to_with<W: Write>(&self, writer: W, mode: ComprMode) -> Result<usize> {
+                    self.object.save_to_with(writer, mode)
+                }
+            }
+        )+
+    };
+}
+
+impl_serializable!(Ciphertext, PublicKey, RelinearizationKeys, GaloisKeys);
+
+/// The `*_SaveSize` functions of the C API
+pub(crate) type SaveSizeFn = unsafe extern "C" fn(*mut c_void, u8, *mut i64) -> c_long;
+/// The `*_Save` functions of the C API
+pub(crate) type SaveFn = unsafe extern "C" fn(*mut c_void, *mut u8, u64, u8, *mut i64) -> c_long;
+
+/// Save an object with its `*_SaveSize` and `*_Save` functions
+pub(crate) fn save(
+    ptr: *mut c_void,
+    mode: ComprMode,
+    save_size: SaveSizeFn,
+    save: SaveFn,
+    name: &str,
+) -> Result<Vec<u8>> {
+    if !mode.is_supported()? {
+        return Err(SealError::invalid(
+            format!("saving the {}", name),
+            format!(
+                "the compression mode {:?} is not supported by this build",
+                mode
+            ),
+        ));
+    }
+    let mut uncompressed_size: i64 = 0;
+    let ret = unsafe { save_size(ptr, mode.into(), &mut uncompressed_size) };
+    check_hresult!(ret, "estimating the save size for the {}", name);
+    let mut actual_size = 0i64;
+    let mut bytes: Vec<u8> = vec![0u8; uncompressed_size as usize];
+    let ret = unsafe {
+        save(
+            ptr,
+            bytes.as_mut_ptr(),
+            uncompressed_size as u64,
+            mode.into(),
+            &mut actual_size,
+        )
+    };
+    check_hresult!(ret, "saving the {}", name);
+    // if compression is 'on', the actual size
+    // will be less than the uncompressed_size
+    bytes.truncate(actual_size as usize);
+    Ok(bytes)
+}
+
+/// The `*_Load` and `*_UnsafeLoad` functions of the C API
+pub(crate) type LoadFn =
+    unsafe extern "C" fn(*mut c_void, *mut c_void, *mut u8, u64, *mut i64) -> c_long;
+/// The `ValCheck_*_IsValidFor` functions of the C API
+pub(crate) type IsValidForFn = unsafe extern "C" fn(*mut c_void, *mut c_void, *mut c_int) -> c_long;
+
+/// Check an object against the parameters of a context with its
+/// `ValCheck_*_IsValidFor` function: its parms id must be in the chain of
+/// the context and its data well formed for those parameters
+pub(crate) fn is_valid_for(
+    ptr: *mut c_void,
+    context: &Context,
+    is_valid_for: IsValidForFn,
+    name: &str,
+) -> Result<bool> {
+    let mut valid = 0;
+    let ret = unsafe { is_valid_for(ptr, context.ptr(), &mut valid) };
+    check_hresult!(ret, "validating the {}", name);
+    Ok(valid != 0)
+}
+
+/// Write the saved bytes of an object and return their number
+pub(crate) fn write<W: Write>(mut writer: W, bytes: &[u8], name: &str) -> Result<usize> {
+    writer
+        .write_all(bytes)
+        .map_err(|err| SealError::stream(format!("writing the {}", name), err))?;
+    Ok(bytes.len())
+}
+
+/// Read the bytes of an object saved by SEAL: the header first, which holds
+/// the total size, then the rest of the object. Nothing is read past the
+/// object, so several objects can be read from the same stream.
+///
+/// The buffer grows with the bytes actually read rather than with the size
+/// claimed by the header, which an untrusted stream could set to anything.
+pub(crate) fn read<R: Read>(mut reader: R, name: &str) -> Result<Vec<u8>> {
+    let operation = || format!("reading the {}", name);
+    let header_size = SerializationHeader::seal_header_size()?;
+    let mut bytes = vec![0u8; header_size];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|err| SealError::stream(operation(), err))?;
+    let header = SerializationHeader::inspect(&bytes)?;
+    reader
+        .by_ref()
+        .take(header.size - header_size as u64)
+        .read_to_end(&mut bytes)
+        .map_err(|err| SealError::stream(operation(), err))?;
+    if (bytes.len() as u64) < header.size {
+        return Err(SealError::stream(
+            operation(),
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "the stream ended after {} of the {} bytes of the object",
+                    bytes.len(),
+                    header.size
+                ),
+            ),
+        ));
+    }
+    Ok(bytes)
+}
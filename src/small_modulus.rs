@@ -1,8 +1,9 @@
 use std::os::raw::*;
 
-use anyhow::Result;
-
-use crate::seal_bindings::*;
+use crate::{
+    error::{check_hresult, Result},
+    seal_bindings::*,
+};
 
 pub struct SmallModulus {
     pub(crate) ptr: *mut c_void,
@@ -12,7 +13,7 @@ impl SmallModulus {
     pub fn create(value: u64) -> Result<SmallModulus> {
         let mut ptr: *mut c_void = std::ptr::null_mut();
         let ret = unsafe { Modulus_Create1(value, &mut ptr) };
-        anyhow::ensure!(ret == 0, "Error creating the small modulus");
+        check_hresult!(ret, "creating the small modulus");
         Ok(SmallModulus { ptr })
     }
 
@@ -27,7 +28,7 @@ impl SmallModulus {
                 &mut ptr,
             )
         };
-        anyhow::ensure!(ret == 0, "Error creating the small modulus");
+        check_hresult!(ret, "creating the small modulus");
         // the first element is a SmallModulus
         Ok(SmallModulus { ptr })
     }
@@ -35,10 +36,7 @@ impl SmallModulus {
     pub fn value(&self) -> Result<u64> {
         let mut value: u64 = 0;
         let ret = unsafe { Modulus_Value(self.ptr, &mut value) };
-        anyhow::ensure!(
-            ret == 0,
-            "Error extracting the value from the small modulus"
-        );
+        check_hresult!(ret, "extracting the value from the small modulus");
         Ok(value)
     }
 }
@@ -51,6 +49,10 @@ impl Drop for SmallModulus {
     }
 }
 
+// A modulus is never modified once created
+unsafe impl Send for SmallModulus {}
+unsafe impl Sync for SmallModulus {}
+
 #[derive(Debug)]
 #[allow(dead_code)]
 pub enum ModulusError {
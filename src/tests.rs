@@ -8,11 +8,11 @@ use super::*;
 #[test]
 /// See 'fn mul_plain' in 'evaluator.rs'
 fn test_transparent() -> Result<()> {
-    let params = Params::create(SCHEME_CKKS)?;
+    let params = Params::create(Scheme::Ckks)?;
     params.set_poly_modulus_degree(8192)?;
     let mut bits_sizes = vec![60, 30, 30, 30, 60]; // sum is 140
     params.set_coeff_modulus_ckks(&mut bits_sizes)?;
-    let context = Context::create(params, 128u8, true)?;
+    let context = Context::create(params, SecurityLevel::Tc128, true)?;
 
     let scale = 2.0_f64.powi(40);
     // Key Generation
@@ -40,23 +40,24 @@ fn test_transparent() -> Result<()> {
 
     // must failed due to transparency
     let enc_cost = evaluator.mul_plain(&price_opt_encrypted, &kwh_cost_plain);
-    assert_eq!(
-        enc_cost.err().unwrap().to_string(),
-        "Transparent output: plaintext must be non zero"
-    );
+    let error = enc_cost.err().unwrap();
+    assert!(matches!(error, SealError::TransparentCiphertext { .. }));
+    assert_eq!(error.code(), Some(0x80131509));
+    assert_eq!(error.operation(), "multiplying a plain text");
+    assert!(error.to_string().contains("plaintext must be non zero"));
     Ok(())
 }
 
 #[test]
 /// See https://github.com/microsoft/SEAL/blob/master/native/examples/4_ckks_basics.cpp
 fn test_ckks_valoconso() -> Result<()> {
-    let params = Params::create(SCHEME_CKKS)?;
+    let params = Params::create(Scheme::Ckks)?;
     params.set_poly_modulus_degree(8192)?;
     //assert_eq!(8192 * 2, params.get_poly_modulus_degree()?);
     // nb mul + 2
     let mut bits_sizes = vec![60, 30, 30, 30, 60]; // sum is 140
     params.set_coeff_modulus_ckks(&mut bits_sizes)?;
-    let context = Context::create(params, 128u8, true)?;
+    let context = Context::create(params, SecurityLevel::Tc128, true)?;
     let primes = context.get_coeff_modulus()?;
     debug!("{:?}", primes);
     let p_0 = primes[3] as f64;
@@ -221,8 +222,8 @@ fn test_ckks_valoconso() -> Result<()> {
     let mut selector = vec![0.0_f64; slot_count];
     selector[0] = 1.0;
     let selector_plain = ckks_encoder.encode(&mut selector, &p_2)?;
-    let mut parms = final_val.parms_id()?;
-    let selector_plain = evaluator.mod_switch_to_plain_text(&selector_plain, &mut parms)?;
+    let parms = final_val.parms_id()?;
+    let selector_plain = evaluator.mod_switch_to_plain_text(&selector_plain, &parms)?;
     let final_val = evaluator.mul_plain(&final_val, &selector_plain)?;
     let final_val = evaluator.relinearize(&final_val, &relinearization_keys)?;
     let final_val = evaluator.rescale_to_next(&final_val)?;
@@ -264,9 +265,9 @@ fn test_ckks_valoconso() -> Result<()> {
 }
 
 #[test]
-fn test_bgv_simple() -> Result<()> {
-    let params = Params::create(SCHEME_BFV)?;
-    let security_level = 128u8;
+fn test_bfv_simple() -> Result<()> {
+    let params = Params::create(Scheme::Bfv)?;
+    let security_level = SecurityLevel::Tc128;
     params.set_poly_modulus_degree(4096)?;
     assert_eq!(4096, params.get_poly_modulus_degree()?);
     params.set_coeff_modulus(&params.bfv_default(security_level)?)?;
@@ -334,9 +335,9 @@ fn test_bgv_simple() -> Result<()> {
 }
 
 #[test]
-fn test_bgv_batch_encoder() -> Result<()> {
-    let params = Params::create(SCHEME_BFV)?;
-    let security_level = 128u8;
+fn test_bfv_batch_encoder() -> Result<()> {
+    let params = Params::create(Scheme::Bfv)?;
+    let security_level = SecurityLevel::Tc128;
     let poly_modulus_degree = 4096usize;
     params.set_poly_modulus_degree(poly_modulus_degree)?;
     assert_eq!(poly_modulus_degree, params.get_poly_modulus_degree()?);
@@ -410,17 +411,189 @@ fn test_bgv_batch_encoder() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_bgv_simple() -> Result<()> {
+    let params = Params::create(Scheme::Bgv)?;
+    let security_level = SecurityLevel::Tc128;
+    params.set_poly_modulus_degree(4096)?;
+    assert_eq!(4096, params.get_poly_modulus_degree()?);
+    params.set_coeff_modulus(&params.bfv_default(security_level)?)?;
+    params.set_plain_modulus(1024)?;
+    let context = Context::create(params, security_level, true)?;
+    assert_eq!(Scheme::Bgv, context.parameters()?.scheme()?);
+    // Key Generation
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    // encryption // decryption
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    // create a constant plain text in the thread local memory pool
+    let value_a = 6u64;
+    let plain_text_a = Plaintext::create_constant(value_a)?;
+    let cipher_text_a = encryptor.encrypt(&plain_text_a)?;
+    let value_b = 7u64;
+    let plain_text_b = Plaintext::create_constant(value_b)?;
+    let cipher_text_b = encryptor.encrypt(&plain_text_b)?;
+    // check the encryption
+    let recovered_a = decryptor.decrypt(&cipher_text_a)?;
+    assert_eq!(value_a, recovered_a.coeff_at(0)?);
+    let recovered_b = decryptor.decrypt(&cipher_text_b)?;
+    assert_eq!(value_b, recovered_b.coeff_at(0)?);
+    //
+    // Operations on cipher text - create an evaluator
+    let evaluator = Evaluator::create(&context)?;
+    // test addition of cipher texts
+    let ct_a_plus_b = evaluator.add(&cipher_text_a, &cipher_text_b)?;
+    assert_eq!(
+        value_a + value_b,
+        decryptor.decrypt(&ct_a_plus_b)?.coeff_at(0)?
+    );
+    // test addition with plain
+    let ct_a_plus_plain_b = evaluator.add_plain(&cipher_text_a, &plain_text_b)?;
+    assert_eq!(
+        value_a + value_b,
+        decryptor.decrypt(&ct_a_plus_plain_b)?.coeff_at(0)?
+    );
+    // test multiplication of cipher texts
+    let ct_a_mul_b = evaluator.mul(&cipher_text_a, &cipher_text_b)?;
+    assert_eq!(
+        value_a * value_b,
+        decryptor.decrypt(&ct_a_mul_b)?.coeff_at(0)?
+    );
+    // test multiplication with plain
+    let ct_a_mul_plain_b = evaluator.mul_plain(&cipher_text_a, &plain_text_b)?;
+    assert_eq!(
+        value_a * value_b,
+        decryptor.decrypt(&ct_a_mul_plain_b)?.coeff_at(0)?
+    );
+    // test relinearization
+    let ct_a_mul_b_relin = evaluator.relinearize(
+        &evaluator.mul(&cipher_text_a, &cipher_text_b)?,
+        &relinearization_keys,
+    )?;
+    assert_eq!(
+        value_a * value_b,
+        decryptor.decrypt(&ct_a_mul_b_relin)?.coeff_at(0)?
+    );
+    // test modulus switching
+    let ct_switched = evaluator.mod_switch_to_next(&ct_a_mul_b_relin)?;
+    assert_eq!(
+        value_a * value_b,
+        decryptor.decrypt(&ct_switched)?.coeff_at(0)?
+    );
+
+    //done
+    Ok(())
+}
+
+#[test]
+fn test_bgv_batch_encoder() -> Result<()> {
+    let params = Params::create(Scheme::Bgv)?;
+    let security_level = SecurityLevel::Tc128;
+    let poly_modulus_degree = 8192usize;
+    params.set_poly_modulus_degree(poly_modulus_degree)?;
+    params.set_coeff_modulus(&params.bfv_default(security_level)?)?;
+    let plain_modulus = SmallModulus::for_batching(poly_modulus_degree, 20)?.value()?;
+    params.set_plain_modulus(plain_modulus)?;
+    let context = Context::create(params, security_level, true)?;
+    // Key Generation
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    // Batch Encoding
+    let batch_encoder = BatchEncoder::create(&context)?;
+    let slots = batch_encoder.slot_count()?;
+    let mut values: Vec<u64> = (0..slots as u64).collect();
+    let plain_text = batch_encoder.encode(&mut values)?;
+    let cipher_text = encryptor.encrypt(&plain_text)?;
+    // x^2 + x
+    let squared = evaluator.relinearize(&evaluator.square(&cipher_text)?, &relinearization_keys)?;
+    let result = evaluator.add(&squared, &cipher_text)?;
+    // BGV noise budget does not need a rescale: switch the modulus to shrink the
+    // cipher text
+    let result = evaluator.mod_switch_to_next(&result)?;
+    let decoded = batch_encoder.decode(&decryptor.decrypt(&result)?)?;
+    for (v, d) in values.iter().zip(decoded.iter()) {
+        assert_eq!((v * v + v) % plain_modulus, *d);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_bgv_mod_reduce() -> Result<()> {
+    let params = Params::create(Scheme::Bgv)?;
+    let security_level = SecurityLevel::Tc128;
+    let poly_modulus_degree = 8192usize;
+    params.set_poly_modulus_degree(poly_modulus_degree)?;
+    params.set_coeff_modulus(&params.bfv_default(security_level)?)?;
+    let plain_modulus = SmallModulus::for_batching(poly_modulus_degree, 20)?.value()?;
+    params.set_plain_modulus(plain_modulus)?;
+    let context = Context::create(params, security_level, true)?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let batch_encoder = BatchEncoder::create(&context)?;
+    let mut values: Vec<u64> = (0..batch_encoder.slot_count()? as u64).collect();
+    let cipher_text = encryptor.encrypt(&batch_encoder.encode(&mut values)?)?;
+
+    // dropping a prime shrinks the cipher text and keeps it decryptable
+    let reduced = evaluator.mod_reduce_to_next(&cipher_text)?;
+    assert_eq!(
+        cipher_text.get_coeff_modulus_length()? - 1,
+        reduced.get_coeff_modulus_length()?
+    );
+    assert!(reduced.save()?.len() < cipher_text.save()?.len());
+    assert_eq!(values, batch_encoder.decode(&decryptor.decrypt(&reduced)?)?);
+
+    // reduce straight to a lower level
+    let parms_id = evaluator.mod_switch_to_next(&reduced)?.parms_id()?;
+    let reduced_to = evaluator.mod_reduce_to(&cipher_text, &parms_id)?;
+    assert_eq!(parms_id, reduced_to.parms_id()?);
+    assert_eq!(
+        values,
+        batch_encoder.decode(&decryptor.decrypt(&reduced_to)?)?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_bfv_mod_reduce_fails() -> Result<()> {
+    let params = Params::create(Scheme::Bfv)?;
+    let security_level = SecurityLevel::Tc128;
+    params.set_poly_modulus_degree(4096)?;
+    params.set_coeff_modulus(&params.bfv_default(security_level)?)?;
+    params.set_plain_modulus(1024)?;
+    let context = Context::create(params, security_level, true)?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let cipher_text = encryptor.encrypt(&Plaintext::create_constant(6)?)?;
+    assert!(evaluator.mod_reduce_to_next(&cipher_text).is_err());
+    Ok(())
+}
+
 #[test]
 /// See https://github.com/microsoft/SEAL/blob/master/native/examples/2_encoders.cpp
 fn test_ckks_simple() -> Result<()> {
-    let params = Params::create(SCHEME_CKKS)?;
+    let params = Params::create(Scheme::Ckks)?;
     params.set_poly_modulus_degree(8192)?;
     assert_eq!(8192, params.get_poly_modulus_degree()?);
     let mut bits_sizes = vec![40, 40, 40, 40, 40];
     params.set_coeff_modulus_ckks(&mut bits_sizes)?;
 
     // Context
-    let context = Context::create(params, 128u8, true)?;
+    let context = Context::create(params, SecurityLevel::Tc128, true)?;
 
     // Key Generation
     let key_generator = KeyGenerator::create(&context)?;
@@ -460,14 +633,14 @@ fn test_ckks_simple() -> Result<()> {
 
 #[test]
 fn test_ckks_sum() -> Result<()> {
-    let params = Params::create(SCHEME_CKKS)?;
+    let params = Params::create(Scheme::Ckks)?;
     params.set_poly_modulus_degree(8192)?;
     assert_eq!(8192, params.get_poly_modulus_degree()?);
     let mut bits_sizes = vec![40, 40, 40, 40, 40];
     params.set_coeff_modulus_ckks(&mut bits_sizes)?;
 
     // Context
-    let context = Context::create(params, 128u8, true)?;
+    let context = Context::create(params, SecurityLevel::Tc128, true)?;
 
     // Key Generation
     let key_generator = KeyGenerator::create(&context)?;
@@ -542,7 +715,7 @@ fn test_ckks_blinded_sum_real() -> Result<()> {
 }
 
 fn ckks_blinded_sum(limit: usize, fast: bool) -> Result<()> {
-    let params = Params::create(SCHEME_CKKS)?;
+    let params = Params::create(Scheme::Ckks)?;
     let poly_modulus_degree = 8192;
     params.set_poly_modulus_degree(poly_modulus_degree)?;
     assert_eq!(poly_modulus_degree, params.get_poly_modulus_degree()?);
@@ -551,7 +724,7 @@ fn ckks_blinded_sum(limit: usize, fast: bool) -> Result<()> {
     let scale = 2.0_f64.powi(40);
 
     // Context
-    let context = Context::create(params, 128u8, true)?;
+    let context = Context::create(params, SecurityLevel::Tc128, true)?;
 
     // Key Generation
     let key_generator = KeyGenerator::create(&context)?;
@@ -610,7 +783,7 @@ fn ckks_blinded_sum(limit: usize, fast: bool) -> Result<()> {
 #[test]
 /// See https://github.com/microsoft/SEAL/blob/master/native/examples/4_ckks_basics.cpp
 fn test_ckks_polynomial() -> Result<()> {
-    let params = Params::create(SCHEME_CKKS)?;
+    let params = Params::create(Scheme::Ckks)?;
     params.set_poly_modulus_degree(8192)?;
     assert_eq!(8192, params.get_poly_modulus_degree()?);
     let mut bits_sizes = vec![60, 40, 40, 60];
@@ -623,7 +796,7 @@ fn test_ckks_polynomial() -> Result<()> {
     // scale stabilization as described above.
     let scale = 2.0_f64.powi(40);
 
-    let context = Context::create(params, 128u8, true)?;
+    let context = Context::create(params, SecurityLevel::Tc128, true)?;
     // Key Generation
     let key_generator = KeyGenerator::create(&context)?;
     let public_key = key_generator.public_key()?;
@@ -713,7 +886,7 @@ fn test_ckks_polynomial() -> Result<()> {
         "    + Scale of PI*x^3 before rescale: {} bits",
         x3_encrypted.scale()?.log2()
     );
-    let x3_encrypted = evaluator.rescale_to_next(&x3_encrypted)?;
+    let mut x3_encrypted = evaluator.rescale_to_next(&x3_encrypted)?;
     debug!(
         "    + Scale of PI*x^3 after rescale: {} bits",
         x3_encrypted.scale()?.log2()
@@ -727,7 +900,7 @@ fn test_ckks_polynomial() -> Result<()> {
         "    + Scale of 0.4*x before rescale: {} bits",
         x1_encrypted.scale()?.log2()
     );
-    let x1_encrypted = evaluator.rescale_to_next(&x1_encrypted)?;
+    let mut x1_encrypted = evaluator.rescale_to_next(&x1_encrypted)?;
     debug!(
         "    + Scale of 0.4*x after rescale: {} bits",
         x1_encrypted.scale()?.log2()
@@ -752,9 +925,9 @@ fn test_ckks_polynomial() -> Result<()> {
     // switch away parts of the coefficient modulus when it is simply not
     // needed.
     debug!("Normalize encryption parameters to the lowest level.");
-    let mut parms_id = x3_encrypted.parms_id()?;
-    let x1_encrypted = evaluator.mod_switch_to(&x1_encrypted, &mut parms_id)?;
-    let plain_coeff0 = evaluator.mod_switch_to_plain_text(&plain_coeff0, &mut parms_id)?;
+    let parms_id = x3_encrypted.parms_id()?;
+    let x1_encrypted = evaluator.mod_switch_to(&x1_encrypted, &parms_id)?;
+    let plain_coeff0 = evaluator.mod_switch_to_plain_text(&plain_coeff0, &parms_id)?;
 
     // All three ciphertexts are now compatible and can be added
     debug!("Compute PI*x^3 + 0.4*x + 1.");
@@ -784,8 +957,8 @@ fn test_ckks_polynomial() -> Result<()> {
 
 #[test]
 fn test_serialization() -> Result<()> {
-    let params = Params::create(SCHEME_BFV)?;
-    let security_level = 128u8;
+    let params = Params::create(Scheme::Bfv)?;
+    let security_level = SecurityLevel::Tc128;
     params.set_poly_modulus_degree(4096)?;
     assert_eq!(4096, params.get_poly_modulus_degree()?);
     params.set_coeff_modulus(&params.bfv_default(security_level)?)?;
@@ -814,8 +987,8 @@ fn test_serialization() -> Result<()> {
     // Everything below is recreated from serialized elements
     //
     // Recreate the context
-    let params = Params::create(SCHEME_BFV)?;
-    let security_level = 128u8;
+    let params = Params::create(Scheme::Bfv)?;
+    let security_level = SecurityLevel::Tc128;
     params.set_poly_modulus_degree(4096)?;
     assert_eq!(4096, params.get_poly_modulus_degree()?);
     params.set_coeff_modulus(&params.bfv_default(security_level)?)?;
@@ -852,133 +1025,1371 @@ fn test_serialization() -> Result<()> {
 }
 
 #[test]
-#[ignore]
-fn test_noise_budget() -> Result<()> {
-    // create a modulus for batching
-    let security_level = 128u8;
-    debug!("|--------|------------|------|------|---------|------|------|---------|------|");
-    debug!("| degree |    modulus | bits | m.p. | µs/slot |  ms  | mul. | µs/slot |  ms  |");
-    for bits_size in 22u8..30u8 {
-        debug!("|--------|------------|------|------|---------|------|------|---------|------|");
-        for degree_index in 2..4 {
-            let poly_modulus_degree = 4096usize * (1usize << degree_index);
-            print!("|{:>7} |", poly_modulus_degree);
-            let slots = poly_modulus_degree;
-            let plain_modulus = match SmallModulus::for_batching(poly_modulus_degree, bits_size) {
-                Ok(sm) => sm.value()?,
-                Err(_) => {
-                    debug!(" FAILURE creating the modulus-------|------|------|---------|------|");
-                    continue;
-                }
-            };
-            print!("{:>11} |{:>5} |", plain_modulus, bits_size);
-            let mut vector: Vec<u64> = Vec::with_capacity(slots);
-            for _col in 0..slots {
-                vector.push(thread_rng().gen::<u64>() % plain_modulus);
-            }
-
-            let params = Params::create(SCHEME_BFV)?;
-            params.set_poly_modulus_degree(poly_modulus_degree)?;
-            assert_eq!(poly_modulus_degree, params.get_poly_modulus_degree()?);
-            params.set_coeff_modulus(&params.bfv_default(security_level)?)?;
-            params.set_plain_modulus(plain_modulus)?;
-            let context = Context::create(params, security_level, true)?;
-
-            // Key Generation
-            let key_generator = KeyGenerator::create(&context)?;
-            let public_key = key_generator.public_key()?;
-            let secret_key = key_generator.secret_key()?;
-            let relinearization_keys = key_generator.relinearization_keys()?;
-
-            // Batch Encoding
-            let batch_encoder = BatchEncoder::create(&context)?;
-            assert_eq!(slots, batch_encoder.slot_count()?);
-            let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
-            let decryptor = Decryptor::create(&context, &secret_key)?;
-
-            // check that the plain text decodes properly and create the sliding vector
-            let plain_text = batch_encoder.encode(&mut vector)?;
-            let v = batch_encoder.decode(&plain_text)?;
-            assert_eq!(vector, v);
-
-            let original_cipher_text = encryptor.encrypt(&plain_text)?;
-
-            // perform multiplications
-            let evaluator = Evaluator::create(&context)?;
-            let loops = 5usize;
-            let mut mul_plain_round = 1usize;
-            let mut mul_plain_nanos = 0u128;
-            let mut mul_round = 1usize;
-            let mut mul_nanos = 0u128;
-            for _l in 0..loops {
-                let mut mul_plain_cipher_text = original_cipher_text.clone()?;
-                let mut mul_cipher_text = original_cipher_text.clone()?;
-                loop {
-                    let mul_plain_now = Instant::now();
-                    mul_plain_cipher_text =
-                        evaluator.mul_plain(&mul_plain_cipher_text, &plain_text)?; //&original_cipher_text)?;
-                    mul_plain_cipher_text =
-                        evaluator.relinearize(&mul_plain_cipher_text, &relinearization_keys)?;
-                    let mul_plain_noise_budget =
-                        decryptor.invariant_noise_budget(&mul_plain_cipher_text)?;
-                    if mul_plain_noise_budget > 0 {
-                        mul_plain_nanos += mul_plain_now.elapsed().as_nanos();
-                        mul_plain_round += 1;
-                    }
-                    //
-                    let mul_now = Instant::now();
-                    mul_cipher_text = evaluator.mul(&mul_cipher_text, &original_cipher_text)?; //&original_cipher_text)?;
-                    mul_cipher_text =
-                        evaluator.relinearize(&mul_cipher_text, &relinearization_keys)?;
-                    let mul_noise_budget = decryptor.invariant_noise_budget(&mul_cipher_text)?;
-                    if mul_noise_budget > 0 {
-                        mul_nanos += mul_now.elapsed().as_nanos();
-                        mul_round += 1;
-                    }
-                    if mul_noise_budget <= 0 && mul_plain_noise_budget <= 0 {
-                        break;
-                    }
-                }
-            }
-            print!(
-                "  {:>2}  |{:>8} |{:>5} |",
-                mul_plain_round / loops,
-                mul_plain_nanos / (mul_plain_round as u128) / (slots as u128) / 1_000,
-                mul_plain_nanos / (mul_plain_round as u128) / 1_000_000,
-            );
-            debug!(
-                "  {:>2}  |{:>8} |{:>5} |",
-                mul_round / loops,
-                mul_nanos / (mul_round as u128) / (slots as u128) / 1_000,
-                mul_nanos / (mul_round as u128) / 1_000_000,
-            );
+fn test_compression_modes() -> Result<()> {
+    // SEAL prefers zstd, then zlib
+    assert!(ComprMode::None.is_supported()?);
+    let default_mode = ComprMode::default_mode()?;
+    assert!(default_mode.is_supported()?);
+    assert_eq!(cfg!(feature = "zstd"), ComprMode::Zstd.is_supported()?);
+    assert_eq!(cfg!(feature = "zlib"), ComprMode::Zlib.is_supported()?);
+    assert_eq!(ComprMode::Zlib, ComprMode::try_from(1u8)?);
+    assert!(ComprMode::try_from(3u8).is_err());
+
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(4096)
+        .coeff_modulus_default()
+        .plain_modulus(1024)
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let cipher_text = encryptor.encrypt(&Plaintext::create_constant(42)?)?;
+    let uncompressed_size = cipher_text.save_with(ComprMode::None)?.len();
+    for mode in [ComprMode::None, ComprMode::Zlib, ComprMode::Zstd] {
+        if !mode.is_supported()? {
+            assert!(cipher_text.save_with(mode).is_err());
+            continue;
         }
+        let mut saved = cipher_text.save_with(mode)?;
+        if mode != ComprMode::None {
+            // the coefficients do not use all the bits of their words
+            assert!(saved.len() < uncompressed_size);
+        }
+        let loaded = Ciphertext::load(&context, &mut saved)?;
+        assert_eq!(42, decryptor.decrypt(&loaded)?.coeff_at(0)?);
+        let mut saved_params = context.parameters()?.save_with(mode)?;
+        assert_eq!(
+            4096,
+            Params::load(&mut saved_params)?.get_poly_modulus_degree()?
+        );
     }
-    debug!("|--------|------------|------|------|---------|------|------|---------|------|");
+    assert_eq!(
+        cipher_text.save()?.len(),
+        cipher_text.save_with(default_mode)?.len()
+    );
     Ok(())
 }
 
 #[test]
-fn test_memory_pool() -> Result<()> {
-    Plaintext::create_in_pool(MemoryPoolHandle::to_thread_local_pool()?)?;
+fn test_streaming_serialization() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(4096)
+        .coeff_modulus_default()
+        .plain_modulus_batching(20)
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let galois_keys = key_generator.galois_keys()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let plain_text = Plaintext::create_constant(42)?;
+    let cipher_text = encryptor.encrypt(&plain_text)?;
+
+    // several objects back to back in the same stream
+    let mut stream = Vec::new();
+    let mut written = context.parameters()?.save_to(&mut stream)?;
+    written += public_key.save_to(&mut stream)?;
+    written += secret_key.save_to(&mut stream)?;
+    written += relinearization_keys.save_to(&mut stream)?;
+    written += galois_keys.save_to(&mut stream)?;
+    written += plain_text.save_to(&mut stream)?;
+    written += cipher_text.save_to(&mut stream)?;
+    assert_eq!(written, stream.len());
+
+    let mut reader = std::io::Cursor::new(&stream);
+    let params = Params::load_from(&mut reader)?;
+    assert_eq!(4096, params.get_poly_modulus_degree()?);
+    let recovered_context = Context::create(params, SecurityLevel::Tc128, true)?;
+    PublicKey::load_from(&recovered_context, &mut reader)?;
+    let recovered_secret_key = SecretKey::load_from(&recovered_context, &mut reader)?;
+    RelinearizationKeys::load_from(&recovered_context, &mut reader)?;
+    let recovered_galois_keys = GaloisKeys::load_from(&recovered_context, &mut reader)?;
+    assert_eq!(galois_keys.save()?, recovered_galois_keys.save()?);
+    let recovered_plain_text = Plaintext::load_from(&recovered_context, &mut reader)?;
+    assert_eq!(42, recovered_plain_text.coeff_at(0)?);
+    let recovered_cipher_text = Ciphertext::load_from(&recovered_context, &mut reader)?;
+    assert_eq!(stream.len() as u64, reader.position());
+    let decryptor = Decryptor::create(&recovered_context, &recovered_secret_key)?;
+    assert_eq!(42, decryptor.decrypt(&recovered_cipher_text)?.coeff_at(0)?);
+
+    // the compression mode can be chosen when streaming too
+    let mut stream = Vec::new();
+    let written = galois_keys.save_to_with(&mut stream, ComprMode::None)?;
+    assert_eq!(galois_keys.save_with(ComprMode::None)?.len(), written);
+    assert_eq!(
+        ComprMode::None,
+        SerializationHeader::inspect(&stream)?.compr_mode
+    );
+    let loaded = GaloisKeys::load_from(&context, &stream[..])?;
+    assert_eq!(galois_keys.save()?, loaded.save()?);
+
+    // truncated stream
+    let saved = cipher_text.save()?;
+    let err = Ciphertext::load_from(&context, &saved[..saved.len() - 1])
+        .err()
+        .expect("the stream is truncated");
+    assert!(matches!(
+        err,
+        SealError::Stream {
+            kind: std::io::ErrorKind::UnexpectedEof,
+            ..
+        }
+    ));
+    // a header claiming a huge object is not trusted to allocate its size
+    let mut crafted = saved[..16].to_vec();
+    crafted[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+    let err = Ciphertext::load_from(&context, &crafted[..])
+        .err()
+        .expect("the stream is shorter than the header claims");
+    assert!(matches!(
+        err,
+        SealError::Stream {
+            kind: std::io::ErrorKind::UnexpectedEof,
+            ..
+        }
+    ));
+    // not a SEAL object
+    let err = Ciphertext::load_from(&context, &[0u8; 64][..])
+        .err()
+        .expect("the header is not valid");
+    assert!(err.to_string().contains("invalid SEAL header"), "{}", err);
     Ok(())
 }
 
 #[test]
-fn test_set_params_bfv() -> Result<()> {
-    let security_level = 128u8;
+fn test_serialization_header() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(4096)
+        .coeff_modulus_default()
+        .plain_modulus(1024)
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let cipher_text = encryptor.encrypt(&Plaintext::create_constant(42)?)?;
+    let saved = cipher_text.save_with(ComprMode::None)?;
+
+    let header = SerializationHeader::inspect(&saved)?;
+    assert_eq!(SerializationHeader::seal_magic()?, header.magic);
+    assert_eq!(
+        SerializationHeader::seal_header_size()?,
+        header.header_size as usize
+    );
+    assert_eq!((4, 1), (header.version_major, header.version_minor));
+    assert_eq!(ComprMode::None, header.compr_mode);
+    assert_eq!(saved.len() as u64, header.size);
+    // only the header is needed
+    assert_eq!(header, SerializationHeader::inspect(&saved[..16])?);
+
+    let inspect_error = |bytes: &[u8]| -> String {
+        SerializationHeader::inspect(bytes)
+            .expect_err("the header should be rejected")
+            .to_string()
+    };
+    let err = inspect_error(&saved[..8]);
+    assert!(err.contains("too short"), "{}", err);
+    let mut corrupted = saved.clone();
+    corrupted[0] ^= 0xFF;
+    let err = inspect_error(&corrupted);
+    assert!(err.contains("magic number"), "{}", err);
+    let mut corrupted = saved.clone();
+    corrupted[3] = 2;
+    let err = inspect_error(&corrupted);
+    assert!(err.contains("version 2.1"), "{}", err);
+    let mut corrupted = saved.clone();
+    corrupted[5] = 7;
+    let err = inspect_error(&corrupted);
+    assert!(err.contains("compression mode"), "{}", err);
+
+    // the header of the params is checked before SEAL loads them
+    let mut corrupted = context.parameters()?.save()?;
+    corrupted[0] ^= 0xFF;
+    let err = Params::load(&mut corrupted)
+        .err()
+        .expect("the header is corrupted")
+        .to_string();
+    assert!(err.contains("magic number"), "{}", err);
+    Ok(())
+}
+
+#[test]
+fn test_serializable() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(4096)
+        .coeff_modulus_default()
+        .plain_modulus_batching(20)
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let batch_encoder = BatchEncoder::create(&context)?;
+    let plain_text = batch_encoder.encode(&mut [3, 4, 5])?;
+
+    // the seeded cipher text is about half the size of a regular one
+    let cipher_text = encryptor.encrypt(&plain_text)?;
+    let seeded: SerializableCiphertext = encryptor.encrypt_symmetric_serializable(&plain_text)?;
+    let full_size = cipher_text.save_with(ComprMode::None)?.len();
+    let mut saved = seeded.save_with(ComprMode::None)?;
+    assert!(
+        saved.len() < full_size * 6 / 10,
+        "{} {}",
+        saved.len(),
+        full_size
+    );
+    let loaded = Ciphertext::load(&context, &mut saved)?;
+    assert_eq!(
+        vec![3, 4, 5],
+        batch_encoder.decode(&decryptor.decrypt(&loaded)?)?[..3]
+    );
+
+    // so are the keys
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let seeded = key_generator.relinearization_keys_serializable()?;
+    let mut saved = Vec::new();
+    seeded.save_to(&mut saved)?;
+    assert!(saved.len() < relinearization_keys.save()?.len() * 6 / 10);
+    let loaded_keys = RelinearizationKeys::load(&context, &mut saved)?;
+    let squared = evaluator.relinearize(&evaluator.mul(&loaded, &loaded)?, &loaded_keys)?;
+    assert_eq!(
+        vec![9, 16, 25],
+        batch_encoder.decode(&decryptor.decrypt(&squared)?)?[..3]
+    );
+    let galois_keys = key_generator.galois_keys()?;
+    let seeded = key_generator.galois_keys_serializable()?;
+    let mut saved = seeded.save()?;
+    assert!(saved.len() < galois_keys.save()?.len() * 6 / 10);
+    let loaded_keys = GaloisKeys::load(&context, &mut saved)?;
+    assert_eq!(galois_keys.parms_id()?, loaded_keys.parms_id()?);
+    Ok(())
+}
+
+#[test]
+fn test_symmetric_encryption() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(4096)
+        .coeff_modulus_default()
+        .plain_modulus(1024)
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let plain_text = Plaintext::create_constant(42)?;
+
+    // the senders only need the public key
+    let public_encryptor = Encryptor::with_public_key(&context, &public_key)?;
+    let public_cipher_text = public_encryptor.encrypt(&plain_text)?;
+    assert_eq!(42, decryptor.decrypt(&public_cipher_text)?.coeff_at(0)?);
+    assert!(matches!(
+        public_encryptor.encrypt_symmetric(&plain_text),
+        Err(SealError::InvalidOperation { .. })
+    ));
+
+    // the owner of the secret key gets less noise
+    let mut secret_encryptor = Encryptor::with_secret_key(&context, &secret_key)?;
+    let secret_cipher_text = secret_encryptor.encrypt_symmetric(&plain_text)?;
+    assert_eq!(42, decryptor.decrypt(&secret_cipher_text)?.coeff_at(0)?);
+    assert!(
+        decryptor.invariant_noise_budget(&secret_cipher_text)?
+            > decryptor.invariant_noise_budget(&public_cipher_text)?
+    );
+    assert!(secret_encryptor.encrypt(&plain_text).is_err());
+    secret_encryptor.set_public_key(&public_key)?;
+    assert_eq!(
+        42,
+        decryptor
+            .decrypt(&secret_encryptor.encrypt(&plain_text)?)?
+            .coeff_at(0)?
+    );
+
+    let zero = secret_encryptor.encrypt_zero_symmetric(None)?;
+    assert_eq!(context.first_parms_id()?, zero.parms_id()?);
+    let decrypted = decryptor.decrypt(&zero)?;
+    for index in 0..decrypted.coeffs_count()? {
+        assert_eq!(0, decrypted.coeff_at(index)?);
+    }
+    let last_parms_id = context.last_parms_id()?;
+    let zero = secret_encryptor.encrypt_zero_symmetric(Some(last_parms_id))?;
+    assert_eq!(last_parms_id, zero.parms_id()?);
+    // without a public key, `encrypt_zero` encrypts with the secret key
+    let secret_encryptor = Encryptor::with_secret_key(&context, &secret_key)?;
+    let zero = secret_encryptor.encrypt_zero(Some(last_parms_id))?;
+    assert_eq!(last_parms_id, zero.parms_id()?);
+    let decrypted = decryptor.decrypt(&zero)?;
+    for index in 0..decrypted.coeffs_count()? {
+        assert_eq!(0, decrypted.coeff_at(index)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_encrypt_zero_rerandomize() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::with_public_key(&context, &public_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+
+    let zero = encryptor.encrypt_zero(None)?;
+    assert_eq!(context.first_parms_id()?, zero.parms_id()?);
+    let last_parms_id = context.last_parms_id()?;
+    let zero = encryptor.encrypt_zero(Some(last_parms_id))?;
+    assert_eq!(last_parms_id, zero.parms_id()?);
+
+    // rerandomize a cipher text which went down the chain
+    let plain_text = ckks_encoder.encode(&mut [1.5, -2.0, 3.25], &2.0_f64.powi(40))?;
+    let cipher_text = evaluator.mod_switch_to_next(&encryptor.encrypt(&plain_text)?)?;
+    let rerandomized = encryptor.rerandomize(&cipher_text)?;
+    assert_eq!(cipher_text.parms_id()?, rerandomized.parms_id()?);
+    assert_eq!(cipher_text.scale()?, rerandomized.scale()?);
+    assert_ne!(cipher_text.save()?, rerandomized.save()?);
+    let decoded = ckks_encoder.decode(&decryptor.decrypt(&rerandomized)?)?;
+    for (expected, actual) in [1.5, -2.0, 3.25].iter().zip(decoded) {
+        assert!((expected - actual).abs() < 1e-4, "{} {}", expected, actual);
+    }
+
+    // without a public key, zero is encrypted with the secret key
+    let secret_encryptor = Encryptor::with_secret_key(&context, &secret_key)?;
+    let rerandomized = secret_encryptor.rerandomize(&cipher_text)?;
+    assert_eq!(cipher_text.parms_id()?, rerandomized.parms_id()?);
+    assert_ne!(cipher_text.save()?, rerandomized.save()?);
+    let decoded = ckks_encoder.decode(&decryptor.decrypt(&rerandomized)?)?;
+    for (expected, actual) in [1.5, -2.0, 3.25].iter().zip(decoded) {
+        assert!((expected - actual).abs() < 1e-4, "{} {}", expected, actual);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_key_generator_from_secret_key() -> Result<()> {
+    let build_context = || {
+        ParamsBuilder::new(Scheme::Bfv)
+            .poly_modulus_degree(4096)
+            .coeff_modulus_default()
+            .plain_modulus(1024)
+            .build()
+    };
+    let context = build_context()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::with_public_key(&context, &public_key)?;
+    let mut saved_cipher_text = encryptor.encrypt(&Plaintext::create_constant(6)?)?.save()?;
+    let mut saved_secret_key = secret_key.save()?;
+    drop(key_generator);
+    drop(context);
+
+    // after the restart
+    let context = build_context()?;
+    let secret_key = SecretKey::load(&context, &mut saved_secret_key)?;
+    let key_generator = KeyGenerator::from_secret_key(&context, &secret_key)?;
+    assert_eq!(saved_secret_key, key_generator.secret_key()?.save()?);
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let cipher_text = Ciphertext::load(&context, &mut saved_cipher_text)?;
+    let evaluator = Evaluator::create(&context)?;
+    let squared = evaluator.relinearize(
+        &evaluator.mul(&cipher_text, &cipher_text)?,
+        &relinearization_keys,
+    )?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    assert_eq!(36, decryptor.decrypt(&squared)?.coeff_at(0)?);
+    // the new public key encrypts for the same secret key
+    let encryptor = Encryptor::with_public_key(&context, &key_generator.public_key()?)?;
+    let cipher_text = encryptor.encrypt(&Plaintext::create_constant(7)?)?;
+    assert_eq!(7, decryptor.decrypt(&cipher_text)?.coeff_at(0)?);
+    Ok(())
+}
+
+#[test]
+fn test_selective_galois_keys() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    // same values as SEAL
+    assert_eq!(3, context.galois_elt_from_step(1)?);
+    assert_eq!(9, context.galois_elt_from_step(2)?);
+    assert_eq!(16383, context.galois_elt_from_step(0)?);
+    assert_eq!(
+        context.galois_elt_from_step(4095)?,
+        context.galois_elt_from_step(-1)?
+    );
+    assert!(context.galois_elt_from_step(4096).is_err());
+    assert_eq!(1, GaloisKeys::index_of(3)?);
+
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+    let scale = 2.0_f64.powi(40);
+    let plain_text = ckks_encoder.encode(&mut [0.0, 1.0, 2.0, 3.0, 4.0], &scale)?;
+    let cipher_text = encryptor.encrypt(&plain_text)?;
+
+    let galois_keys = key_generator.galois_keys_for_steps(&[3, -1])?;
+    assert!(galois_keys.has_key(context.galois_elt_from_step(3)?)?);
+    assert!(galois_keys.has_key(context.galois_elt_from_step(-1)?)?);
+    assert!(!galois_keys.has_key(context.galois_elt_from_step(1)?)?);
+    assert!(!galois_keys.has_key(2)?);
+    assert!(galois_keys.save()?.len() < key_generator.galois_keys()?.save()?.len() / 4);
+    let rotated = evaluator.rotate(&cipher_text, 3, &galois_keys)?;
+    let decoded = ckks_encoder.decode(&decryptor.decrypt(&rotated)?)?;
+    assert!((decoded[0] - 3.0).abs() < 1e-4, "{}", decoded[0]);
+    assert!((decoded[1] - 4.0).abs() < 1e-4, "{}", decoded[1]);
+    // there is no key for this rotation
+    assert!(evaluator.rotate(&cipher_text, 2, &galois_keys).is_err());
+
+    let galois_keys =
+        key_generator.galois_keys_for_elements(&[context.galois_elt_from_step(2)?])?;
+    let rotated = evaluator.rotate(&cipher_text, 2, &galois_keys)?;
+    let decoded = ckks_encoder.decode(&decryptor.decrypt(&rotated)?)?;
+    assert!((decoded[0] - 2.0).abs() < 1e-4, "{}", decoded[0]);
+    Ok(())
+}
+
+#[test]
+fn test_rotation_planner() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    let computation = |evaluator: &Evaluator,
+                       inputs: &[Ciphertext],
+                       relinearization_keys: &RelinearizationKeys,
+                       galois_keys: &GaloisKeys|
+     -> Result<Ciphertext, SealError> {
+        let sum = evaluator.add(&inputs[0], &inputs[1])?;
+        let squared = evaluator.relinearize(&evaluator.mul(&sum, &sum)?, relinearization_keys)?;
+        let rotated = evaluator.rotate(&squared, 5, galois_keys)?;
+        let rotated = evaluator.rotate(&rotated, -3, galois_keys)?;
+        let rotated = evaluator.rotate(&rotated, 5, galois_keys)?;
+        evaluator.complex_conjugate(&rotated, galois_keys)
+    };
+    let plan = RotationPlanner::new(&context).plan(2, computation)?;
+    assert_eq!(
+        vec![
+            Rotation::Steps(-3),
+            Rotation::Steps(5),
+            Rotation::Conjugation
+        ],
+        plan.rotations
+    );
+    let mut expected = vec![
+        context.galois_elt_from_step(-3)?,
+        context.galois_elt_from_step(0)?,
+        context.galois_elt_from_step(5)?,
+    ];
+    expected.sort();
+    assert_eq!(expected, plan.galois_elts);
+
+    // the planned keys are enough for the real computation
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+    let scale = 2.0_f64.powi(40);
+    let values: Vec<f64> = (0..16).map(|i| i as f64 / 4.0).collect();
+    let inputs = [
+        encryptor.encrypt(&ckks_encoder.encode(&mut values.clone(), &scale)?)?,
+        encryptor.encrypt(&ckks_encoder.encode(&mut values.clone(), &scale)?)?,
+    ];
+    let check = |galois_keys: &GaloisKeys| -> Result<()> {
+        let result = computation(&evaluator, &inputs, &relinearization_keys, galois_keys)?;
+        let decoded = ckks_encoder.decode(&decryptor.decrypt(&result)?)?;
+        // (2 x)^2 rotated by 7 slots
+        for (slot, value) in decoded.iter().take(8).enumerate() {
+            let expected = (2.0 * values[slot + 7]).powi(2);
+            assert!((value - expected).abs() < 1e-3, "{} {}", value, expected);
+        }
+        Ok(())
+    };
+    check(&plan.galois_keys(&key_generator)?)?;
+
+    // in non-adjacent form, 5 = 4 + 1 and -3 = -4 + 1
+    let naf_plan = RotationPlanner::new(&context)
+        .naf(true)
+        .plan_rotations(&plan.rotations)?;
+    let mut expected = vec![
+        context.galois_elt_from_step(1)?,
+        context.galois_elt_from_step(4)?,
+        context.galois_elt_from_step(-4)?,
+        context.galois_elt_from_step(0)?,
+    ];
+    expected.sort();
+    assert_eq!(expected, naf_plan.galois_elts);
+    check(&naf_plan.galois_keys(&key_generator)?)?;
+
+    // a rotation by 0 steps is a copy and needs no key, unlike the
+    // conjugation
+    let plan = RotationPlanner::new(&context).plan(
+        1,
+        |evaluator, inputs, _, galois_keys| -> Result<(), SealError> {
+            let rotated = evaluator.rotate(&inputs[0], 0, galois_keys)?;
+            evaluator.rotate(&rotated, 3, galois_keys)?;
+            Ok(())
+        },
+    )?;
+    assert_eq!(vec![Rotation::Steps(3)], plan.rotations);
+    assert_eq!(vec![context.galois_elt_from_step(3)?], plan.galois_elts);
+    let plan = RotationPlanner::new(&context).plan_rotations(&[Rotation::Steps(0)])?;
+    assert!(plan.rotations.is_empty());
+    assert!(plan.galois_elts.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_public_keys() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(4096)
+        .coeff_modulus_default()
+        .plain_modulus(1024)
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let secret_key = key_generator.secret_key()?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+
+    // one fresh public key per client, all for the same secret key
+    let public_key_a = key_generator.public_key()?;
+    let public_key_b = key_generator.public_key()?;
+    assert_eq!(context.key_parms_id()?, public_key_a.parms_id()?);
+    assert!(public_key_a != public_key_b);
+    assert!(public_key_a == public_key_a.clone()?);
+    let mut saved = public_key_a.save()?;
+    assert!(public_key_a == PublicKey::load(&context, &mut saved)?);
+    for public_key in [&public_key_a, &public_key_b] {
+        let encryptor = Encryptor::with_public_key(&context, public_key)?;
+        let cipher_text = encryptor.encrypt(&Plaintext::create_constant(5)?)?;
+        assert_eq!(5, decryptor.decrypt(&cipher_text)?.coeff_at(0)?);
+    }
+
+    // the seeded public key is half the size
+    let seeded: SerializablePublicKey = key_generator.public_key_serializable()?;
+    let mut saved = seeded.save()?;
+    assert!(saved.len() < public_key_a.save()?.len() * 6 / 10);
+    let public_key = PublicKey::load(&context, &mut saved)?;
+    assert!(public_key != public_key_a);
+    let encryptor = Encryptor::with_public_key(&context, &public_key)?;
+    let cipher_text = encryptor.encrypt(&Plaintext::create_constant(8)?)?;
+    assert_eq!(8, decryptor.decrypt(&cipher_text)?.coeff_at(0)?);
+    Ok(())
+}
+
+#[test]
+fn test_key_set() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(4096)
+        .coeff_modulus_default()
+        .plain_modulus_batching(20)
+        .security_level(SecurityLevel::Tc128)
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let key_set = KeySet::new(&context)
+        .with_public_key(key_generator.public_key()?)
+        .with_secret_key(key_generator.secret_key()?)
+        .with_relinearization_keys(key_generator.relinearization_keys()?)
+        .with_galois_keys(key_generator.galois_keys_for_steps(&[1])?);
+    key_set.validate()?;
+
+    // one container for the parameters and all the keys
+    let saved = key_set.save()?;
+    let loaded = KeySet::load(&saved)?;
+    assert_eq!(context.key_parms_id()?, loaded.context().key_parms_id()?);
+    assert_eq!(SecurityLevel::Tc128, loaded.context().security_level());
+    assert!(loaded.public_key() == key_set.public_key());
+    let mut streamed = vec![];
+    assert_eq!(saved.len(), key_set.save_to(&mut streamed)?);
+    assert_eq!(saved, streamed);
+
+    // the server evaluates with the public bundle, the client decrypts
+    let bundle = KeySet::load(&key_set.public_bundle()?.save()?)?;
+    assert!(bundle.secret_key().is_none());
+    assert!(bundle.relinearization_keys().is_some());
+    let server_context = bundle.context();
+    let encoder = BatchEncoder::create(&context)?;
+    let encryptor = Encryptor::with_public_key(server_context, bundle.public_key().unwrap())?;
+    let mut values = vec![0u64; encoder.slot_count()?];
+    values[0] = 3;
+    values[1] = 5;
+    let cipher_text = encryptor.encrypt(&encoder.encode(&mut values)?)?;
+    let evaluator = Evaluator::create(server_context)?;
+    let squared = evaluator.relinearize(
+        &evaluator.square(&cipher_text)?,
+        bundle.relinearization_keys().unwrap(),
+    )?;
+    let rotated = evaluator.rotate_rows(&squared, 1, bundle.galois_keys().unwrap())?;
+    let mut saved = rotated.save()?;
+    let decryptor = Decryptor::create(&context, loaded.secret_key().unwrap())?;
+    let decoded = encoder.decode(&decryptor.decrypt(&Ciphertext::load(&context, &mut saved)?)?)?;
+    assert_eq!(25, decoded[0]);
+
+    // the container is checked before loading the keys
+    let mut corrupted = key_set.save()?;
+    corrupted[0] = b'X';
+    assert!(KeySet::load(&corrupted).is_err());
+    let mut future = key_set.save()?;
+    future[4] = 2;
+    let Err(err) = KeySet::load(&future) else {
+        panic!("unsupported version loaded");
+    };
+    assert!(err.to_string().contains("version 2"), "{}", err);
+    let mut trailing = key_set.save()?;
+    trailing.push(0);
+    assert!(KeySet::load(&trailing).is_err());
+
+    // keys of other parameters are rejected
+    let other_context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_default()
+        .plain_modulus_batching(20)
+        .build()?;
+    let other_key_generator = KeyGenerator::create(&other_context)?;
+    let mismatched = KeySet::new(&context).with_secret_key(other_key_generator.secret_key()?);
+    let err = mismatched.validate().expect_err("keys of other parameters");
+    assert!(err.to_string().contains("secret key"), "{}", err);
+
+    // keys of another secret key for the same parameters are rejected too
+    let other_key_generator = KeyGenerator::create(&context)?;
+    let with_secret_key = || -> Result<KeySet> {
+        Ok(KeySet::new(&context).with_secret_key(key_generator.secret_key()?))
+    };
+    let mismatched = with_secret_key()?.with_public_key(other_key_generator.public_key()?);
+    let err = mismatched
+        .validate()
+        .expect_err("public key of another secret key");
+    assert!(err.to_string().contains("public key"), "{}", err);
+    assert!(KeySet::load(&mismatched.save()?).is_err());
+    let mismatched =
+        with_secret_key()?.with_relinearization_keys(other_key_generator.relinearization_keys()?);
+    let err = mismatched
+        .validate()
+        .expect_err("relinearization keys of another secret key");
+    assert!(err.to_string().contains("relinearization keys"), "{}", err);
+    let mismatched =
+        with_secret_key()?.with_galois_keys(other_key_generator.galois_keys_for_steps(&[1])?);
+    let err = mismatched
+        .validate()
+        .expect_err("Galois keys of another secret key");
+    assert!(err.to_string().contains("Galois keys"), "{}", err);
+    // without the secret key, there is nothing to check them against
+    mismatched.public_bundle()?.validate()?;
+
+    // every Galois key is checked, not only the first one
+    let galois_keys = key_generator.galois_keys_for_steps(&[1, 2])?;
+    let spliced = splice_galois_key(
+        &galois_keys,
+        &other_key_generator.galois_keys_for_steps(&[1, 2])?,
+        context.galois_elt_from_step(2)?,
+    )?;
+    with_secret_key()?
+        .with_galois_keys(galois_keys)
+        .validate()?;
+    let mismatched = with_secret_key()?.with_galois_keys(spliced);
+    let err = mismatched
+        .validate()
+        .expect_err("a Galois key of another secret key");
+    assert!(err.to_string().contains("Galois keys"), "{}", err);
+    assert!(KeySet::load(&mismatched.save()?).is_err());
+    Ok(())
+}
+
+/// The Galois keys with the key of `galois_elt` taken from `other`, as an
+/// untrusted party could put them together
+fn splice_galois_key(keys: &GaloisKeys, other: &GaloisKeys, galois_elt: u32) -> Result<GaloisKeys> {
+    use crate::seal_bindings::*;
+    let spliced = keys.clone()?;
+    let spliced_index = GaloisKeys::index_of(galois_elt)? as u64;
+    let mut raw_size = 0u64;
+    unsafe {
+        assert_eq!(0, KSwitchKeys_RawSize(keys.ptr(), &mut raw_size));
+        assert_eq!(0, KSwitchKeys_ClearDataAndReserve(spliced.ptr(), raw_size));
+    }
+    for index in 0..raw_size {
+        let source = if index == spliced_index { other } else { keys };
+        let mut count = 0u64;
+        let mut key_list = vec![];
+        unsafe {
+            assert_eq!(
+                0,
+                KSwitchKeys_GetKeyList(source.ptr(), index, &mut count, std::ptr::null_mut())
+            );
+            key_list.resize(count as usize, std::ptr::null_mut());
+            // copies of the keys, copied again when added
+            assert_eq!(
+                0,
+                KSwitchKeys_GetKeyList(source.ptr(), index, &mut count, key_list.as_mut_ptr())
+            );
+            assert_eq!(
+                0,
+                KSwitchKeys_AddKeyList(spliced.ptr(), count, key_list.as_mut_ptr())
+            );
+            for key in key_list {
+                PublicKey_Destroy(key);
+            }
+        }
+    }
+    Ok(spliced)
+}
+
+#[test]
+fn test_validation() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(4096)
+        .coeff_modulus_default()
+        .plain_modulus(1024)
+        .build()?;
+    let other_context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_default()
+        .plain_modulus(1024)
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let galois_keys = key_generator.galois_keys_for_steps(&[1])?;
+    assert!(public_key.is_valid_for(&context)?);
+    assert!(secret_key.is_valid_for(&context)?);
+    assert!(relinearization_keys.is_valid_for(&context)?);
+    assert!(galois_keys.is_valid_for(&context)?);
+    assert!(!public_key.is_valid_for(&other_context)?);
+    assert!(!secret_key.is_valid_for(&other_context)?);
+    assert!(!relinearization_keys.is_valid_for(&other_context)?);
+    assert!(!galois_keys.is_valid_for(&other_context)?);
+
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let plain_text = Plaintext::create_constant(7)?;
+    assert!(plain_text.is_valid_for(&context)?);
+    let mut cipher_text = encryptor.encrypt(&plain_text)?;
+    assert!(cipher_text.is_valid_for(&context)?);
+    assert!(!cipher_text.is_valid_for(&other_context)?);
+
+    // trusted bytes, validated separately
+    let mut saved = cipher_text.save()?;
+    let loaded = unsafe { Ciphertext::unsafe_load(&context, &mut saved)? };
+    assert!(loaded.is_valid_for(&context)?);
+    assert_eq!(7, decryptor.decrypt(&loaded)?.coeff_at(0)?);
+    let mut saved = plain_text.save()?;
+    assert!(unsafe { Plaintext::unsafe_load(&context, &mut saved)? }.is_valid_for(&context)?);
+    let mut saved = public_key.save()?;
+    assert!(unsafe { PublicKey::unsafe_load(&context, &mut saved)? } == public_key);
+    let mut saved = secret_key.save()?;
+    assert!(unsafe { SecretKey::unsafe_load(&context, &mut saved)? }.is_valid_for(&context)?);
+    let mut saved = relinearization_keys.save()?;
+    let loaded = unsafe { RelinearizationKeys::unsafe_load(&context, &mut saved)? };
+    assert!(loaded.is_valid_for(&context)?);
+    let mut saved = galois_keys.save()?;
+    let loaded = unsafe { GaloisKeys::unsafe_load(&context, &mut saved)? };
+    assert!(loaded.has_key(context.galois_elt_from_step(1)?)?);
+
+    // coefficients out of the range of their prime
+    let raw_rns = vec![u64::MAX; cipher_text.get_raw_rns()?.len()];
+    cipher_text.set_raw_rns(raw_rns)?;
+    assert!(!cipher_text.is_valid_for(&context)?);
+    let mut saved = cipher_text.save()?;
+    assert!(Ciphertext::load(&context, &mut saved).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_sub_negate() -> Result<()> {
+    for scheme in [Scheme::Bfv, Scheme::Bgv] {
+        let context = ParamsBuilder::new(scheme)
+            .poly_modulus_degree(4096)
+            .coeff_modulus_default()
+            .plain_modulus_batching(20)
+            .build()?;
+        let plain_modulus = context.parameters()?.get_plain_modulus()?;
+        let key_generator = KeyGenerator::create(&context)?;
+        let public_key = key_generator.public_key()?;
+        let secret_key = key_generator.secret_key()?;
+        let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+        let decryptor = Decryptor::create(&context, &secret_key)?;
+        let evaluator = Evaluator::create(&context)?;
+        let encoder = BatchEncoder::create(&context)?;
+        let slot_count = encoder.slot_count()?;
+        let mut rng = thread_rng();
+        let mut a: Vec<u64> = (0..slot_count)
+            .map(|_| rng.gen_range(0..plain_modulus))
+            .collect();
+        let mut b: Vec<u64> = (0..slot_count)
+            .map(|_| rng.gen_range(0..plain_modulus))
+            .collect();
+        let a_plain = encoder.encode(&mut a)?;
+        let b_plain = encoder.encode(&mut b)?;
+        let a_encrypted = encryptor.encrypt(&a_plain)?;
+        let b_encrypted = encryptor.encrypt(&b_plain)?;
+        let expected: Vec<u64> = a
+            .iter()
+            .zip(&b)
+            .map(|(a, b)| (a + plain_modulus - b) % plain_modulus)
+            .collect();
+
+        let decrypt = |cipher_text: &Ciphertext| -> Result<Vec<u64>, SealError> {
+            encoder.decode(&decryptor.decrypt(cipher_text)?)
+        };
+        assert_eq!(
+            expected,
+            decrypt(&evaluator.sub(&a_encrypted, &b_encrypted)?)?
+        );
+        assert_eq!(
+            expected,
+            decrypt(&evaluator.sub_plain(&a_encrypted, &b_plain)?)?
+        );
+        let negated: Vec<u64> = a
+            .iter()
+            .map(|a| (plain_modulus - a) % plain_modulus)
+            .collect();
+        assert_eq!(negated, decrypt(&evaluator.negate(&a_encrypted)?)?);
+        assert!(matches!(
+            evaluator.sub(&a_encrypted, &a_encrypted),
+            Err(SealError::TransparentCiphertext { .. })
+        ));
+    }
+
+    // CKKS: no level is consumed, unlike a multiplication by -1
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+    let scale = 2.0_f64.powi(40);
+    let a_plain = ckks_encoder.encode(&mut [12.5, -3.0, 0.25], &scale)?;
+    let b_plain = ckks_encoder.encode(&mut [2.5, 4.0, 1.0], &scale)?;
+    let a_encrypted = encryptor.encrypt(&a_plain)?;
+    let b_encrypted = encryptor.encrypt(&b_plain)?;
+    let expected = [10.0, -7.0, -0.75];
+    for result in [
+        evaluator.sub(&a_encrypted, &b_encrypted)?,
+        evaluator.sub_plain(&a_encrypted, &b_plain)?,
+        evaluator.add(&a_encrypted, &evaluator.negate(&b_encrypted)?)?,
+    ] {
+        assert_eq!(a_encrypted.parms_id()?, result.parms_id()?);
+        assert_eq!(scale, result.scale()?);
+        let decoded = ckks_encoder.decode(&decryptor.decrypt(&result)?)?;
+        for (value, expected) in decoded.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-5, "{} != {}", value, expected);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_add_multiply_many() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_default()
+        .plain_modulus_batching(20)
+        .build()?;
+    let plain_modulus = context.parameters()?.get_plain_modulus()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let encoder = BatchEncoder::create(&context)?;
+    let slot_count = encoder.slot_count()?;
+    let encrypt = |value: u64| -> Result<Ciphertext, SealError> {
+        let mut values = vec![value; slot_count];
+        values[1] = value + 1;
+        encryptor.encrypt(&encoder.encode(&mut values)?)
+    };
+    let decrypt = |cipher_text: &Ciphertext| -> Result<Vec<u64>, SealError> {
+        encoder.decode(&decryptor.decrypt(cipher_text)?)
+    };
+
+    let cipher_texts = (0..200)
+        .map(encrypt)
+        .collect::<Result<Vec<_>, SealError>>()?;
+    let refs: Vec<&Ciphertext> = cipher_texts.iter().collect();
+    let sum = decrypt(&evaluator.add_many(&refs)?)?;
+    assert_eq!((0..200).sum::<u64>(), sum[0]);
+    assert_eq!((1..201).sum::<u64>(), sum[1]);
+
+    let factors = [2, 3, 5, 7]
+        .into_iter()
+        .map(encrypt)
+        .collect::<Result<Vec<_>, SealError>>()?;
+    let refs: Vec<&Ciphertext> = factors.iter().collect();
+    let product = evaluator.multiply_many(&refs, &relinearization_keys)?;
+    assert_eq!(2, product.size()?);
+    let decoded = decrypt(&product)?;
+    assert_eq!(210, decoded[0]);
+    assert_eq!(3 * 4 * 6 * 8, decoded[1]);
+    assert!(decryptor.invariant_noise_budget(&product)? > 0);
+
+    let power = evaluator.exponentiate(&factors[2], 5, &relinearization_keys)?;
+    assert_eq!(2, power.size()?);
+    let decoded = decrypt(&power)?;
+    assert_eq!(3125, decoded[0]);
+    assert_eq!(6u64.pow(5) % plain_modulus, decoded[1]);
+    let same = evaluator.exponentiate(&factors[2], 1, &relinearization_keys)?;
+    assert_eq!(5, decrypt(&same)?[0]);
+    for exponent in [2, 6, 7] {
+        let power = evaluator.exponentiate(&factors[0], exponent, &relinearization_keys)?;
+        assert_eq!(2u64.pow(exponent as u32), decrypt(&power)?[0]);
+    }
+    // the largest exponents take 2 * 64 multiplications, not a copy of the
+    // cipher text per factor; the noise budget is exhausted long before
+    let power = evaluator.exponentiate(&factors[0], u64::MAX, &relinearization_keys)?;
+    assert_eq!(2, power.size()?);
+    assert_eq!(0, decryptor.invariant_noise_budget(&power)?);
+    evaluator.exponentiate(&factors[0], 1 << 63, &relinearization_keys)?;
+
+    assert!(evaluator.add_many(&[]).is_err());
+    assert!(evaluator
+        .exponentiate(&factors[0], 0, &relinearization_keys)
+        .is_err());
+    let switched = evaluator.mod_switch_to_next(&factors[1])?;
+    assert!(matches!(
+        evaluator.multiply_many(&[&factors[0], &switched], &relinearization_keys),
+        Err(SealError::LevelMismatch { .. })
+    ));
+
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let encryptor = Encryptor::with_secret_key(&context, &key_generator.secret_key()?)?;
+    let evaluator = Evaluator::create(&context)?;
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+    let plain_text = ckks_encoder.encode(&mut [1.5], &2.0_f64.powi(40))?;
+    let cipher_text = encryptor.encrypt_symmetric(&plain_text)?;
+    assert!(evaluator
+        .exponentiate(&cipher_text, 2, &relinearization_keys)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_inplace_operations() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_default()
+        .plain_modulus_batching(20)
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let galois_keys = key_generator.galois_keys_for_steps(&[1])?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let encoder = BatchEncoder::create(&context)?;
+    let mut values: Vec<u64> = (0..encoder.slot_count()? as u64).map(|i| i % 100).collect();
+    let plain_text = encoder.encode(&mut values)?;
+    let a = encryptor.encrypt(&plain_text)?;
+    let b = encryptor.encrypt(&plain_text)?;
+    let decrypt = |cipher_text: &Ciphertext| -> Result<Vec<u64>, SealError> {
+        encoder.decode(&decryptor.decrypt(cipher_text)?)
+    };
+
+    // the same computation, allocating and in place
+    let expected = evaluator.add(&a, &b)?;
+    let expected = evaluator.relinearize(&evaluator.mul(&expected, &a)?, &relinearization_keys)?;
+    let expected = evaluator.sub_plain(&evaluator.square(&expected)?, &plain_text)?;
+    let expected = evaluator.relinearize(&expected, &relinearization_keys)?;
+    let expected =
+        evaluator.mul_plain(&evaluator.add_plain(&expected, &plain_text)?, &plain_text)?;
+    let expected = evaluator.rotate_rows(&evaluator.negate(&expected)?, 1, &galois_keys)?;
+    let expected = evaluator.sub(
+        &evaluator.mod_switch_to_next(&expected)?,
+        &evaluator.mod_switch_to_next(&b)?,
+    )?;
+    let mut result = a.clone()?;
+    evaluator.add_assign(&mut result, &b)?;
+    evaluator.mul_assign(&mut result, &a)?;
+    evaluator.relinearize_inplace(&mut result, &relinearization_keys)?;
+    evaluator.square_inplace(&mut result)?;
+    evaluator.sub_plain_assign(&mut result, &plain_text)?;
+    evaluator.relinearize_inplace(&mut result, &relinearization_keys)?;
+    evaluator.add_plain_assign(&mut result, &plain_text)?;
+    evaluator.mul_plain_assign(&mut result, &plain_text)?;
+    evaluator.negate_inplace(&mut result)?;
+    evaluator.rotate_rows_inplace(&mut result, 1, &galois_keys)?;
+    evaluator.mod_switch_to_next_inplace(&mut result)?;
+    let mut switched = b.clone()?;
+    evaluator.mod_switch_to_inplace(&mut switched, &result.parms_id()?)?;
+    evaluator.sub_assign(&mut result, &switched)?;
+    assert_eq!(expected.parms_id()?, result.parms_id()?);
+    assert_eq!(decrypt(&expected)?, decrypt(&result)?);
+
+    // a refused operation leaves the operand untouched
+    let mut unchanged = a.clone()?;
+    assert!(matches!(
+        evaluator.add_assign(&mut unchanged, &switched),
+        Err(SealError::LevelMismatch { .. })
+    ));
+    assert_eq!(decrypt(&a)?, decrypt(&unchanged)?);
+
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let galois_keys = key_generator.galois_keys_for_steps(&[2, 0])?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::with_secret_key(&context, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+    let scale = 2.0_f64.powi(40);
+    let mut values = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let mut cipher_text =
+        encryptor.encrypt_symmetric(&ckks_encoder.encode(&mut values, &scale)?)?;
+    let other = cipher_text.clone()?;
+    evaluator.mul_assign(&mut cipher_text, &other)?;
+    evaluator.relinearize_inplace(&mut cipher_text, &relinearization_keys)?;
+    evaluator.rescale_to_next_inplace(&mut cipher_text)?;
+    evaluator.rotate_inplace(&mut cipher_text, 2, &galois_keys)?;
+    evaluator.complex_conjugate_inplace(&mut cipher_text, &galois_keys)?;
+    evaluator.mod_reduce_to_next_inplace(&mut cipher_text)?;
+    assert_eq!(context.last_parms_id()?, cipher_text.parms_id()?);
+    let decoded = ckks_encoder.decode(&decryptor.decrypt(&cipher_text)?)?;
+    for (value, expected) in decoded.iter().zip([9.0, 16.0, 25.0]) {
+        assert!((value - expected).abs() < 1e-3, "{} != {}", value, expected);
+    }
+
+    // the rotations in place are recorded too
+    let plan = RotationPlanner::new(&context).plan(
+        1,
+        |evaluator, inputs, _, galois_keys| -> Result<(), SealError> {
+            let mut cipher_text = inputs[0].clone()?;
+            evaluator.rotate_inplace(&mut cipher_text, 3, galois_keys)?;
+            evaluator.complex_conjugate_inplace(&mut cipher_text, galois_keys)
+        },
+    )?;
+    assert_eq!(
+        vec![Rotation::Steps(3), Rotation::Conjugation],
+        plan.rotations
+    );
+    Ok(())
+}
+
+#[test]
+fn test_expressions() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_default()
+        .plain_modulus_batching(20)
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let encoder = BatchEncoder::create(&context)?;
+    let expressions = ExpressionEvaluator::create(&context, key_generator.relinearization_keys()?)?;
+    let encrypt = |first: u64, second: u64| -> Result<EncryptedValue, SealError> {
+        let mut values = vec![first; encoder.slot_count()?];
+        values[1] = second;
+        Ok(expressions.value(encryptor.encrypt(&encoder.encode(&mut values)?)?))
+    };
+    let decrypt = |cipher_text: &Ciphertext| -> Result<Vec<u64>, SealError> {
+        encoder.decode(&decryptor.decrypt(cipher_text)?)
+    };
+    let a = encrypt(3, 4)?;
+    let b = encrypt(5, 6)?;
+    let c = encrypt(7, 2)?;
+    let mut ones = vec![1; encoder.slot_count()?];
+    let plain_text = encoder.encode(&mut ones)?;
+
+    let result = ((&a + &b) * &c - 1u64) * &a + &plain_text;
+    let cipher_text = result.eval()?;
+    // relinearized after each multiplication
+    assert_eq!(2, cipher_text.size()?);
+    let decoded = decrypt(&cipher_text)?;
+    assert_eq!(((3 + 5) * 7 - 1) * 3 + 1, decoded[0]);
+    assert_eq!(((4 + 6) * 2 - 1) * 4 + 1, decoded[1]);
+    let negated = decrypt(&(-(&a * 2u64) + 100u64).eval()?)?;
+    assert_eq!(94, negated[0]);
+    assert_eq!(92, negated[1]);
+
+    // the checked steps
+    let sum = a.try_add(&b)?;
+    let product = sum.try_mul(&c)?.try_sub_plain(&plain_text)?;
+    assert_eq!(55, decrypt(product.cipher_text()?)?[0]);
+    let difference = b.try_sub(&a)?.try_neg()?.try_add_plain(&plain_text)?;
+    let plain_modulus = context.parameters()?.get_plain_modulus()?;
+    assert_eq!(plain_modulus - 1, decrypt(difference.cipher_text()?)?[0]);
+
+    // the first error is carried to `eval`
+    assert!(matches!(
+        (&a - &a).eval(),
+        Err(SealError::TransparentCiphertext { .. })
+    ));
+    let failed = (&a - &a) * 2u64 + &b;
+    assert!(failed.cipher_text().is_err());
+    assert!(matches!(
+        failed.eval(),
+        Err(SealError::TransparentCiphertext { .. })
+    ));
+    assert!(a.try_sub(&a).is_err());
+    let switched = expressions.value(
+        expressions
+            .evaluator()
+            .mod_switch_to_next(b.cipher_text()?)?,
+    );
+    assert!(matches!(
+        a.try_mul(&switched),
+        Err(SealError::LevelMismatch { .. })
+    ));
+    assert!((&a * 1.5).eval().is_err());
+
+    // CKKS
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::with_secret_key(&context, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+    let expressions = ExpressionEvaluator::create(&context, key_generator.relinearization_keys()?)?;
+    let scale = 2.0_f64.powi(40);
+    let x = expressions
+        .value(encryptor.encrypt_symmetric(&ckks_encoder.encode(&mut [1.5, -2.0], &scale)?)?);
+    let y = expressions
+        .value(encryptor.encrypt_symmetric(&ckks_encoder.encode(&mut [0.5, 3.0], &scale)?)?);
+    // (x y - x^2 + 0.25) / 2, rescaled after each multiplication
+    let result = ((&x * &y - &x * &x).rescale_to_next() + 0.25) * 0.5;
+    let result = result.rescale_to_next().eval()?;
+    let decoded = ckks_encoder.decode(&decryptor.decrypt(&result)?)?;
+    for (value, expected) in decoded.iter().zip([-0.625, -4.875]) {
+        assert!((value - expected).abs() < 1e-3, "{} != {}", value, expected);
+    }
+    assert!((&x + 1u64).eval().is_err());
+    Ok(())
+}
+
+#[test]
+#[ignore]
+fn bench_inplace_operations() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_default()
+        .plain_modulus_batching(20)
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let encryptor = Encryptor::with_public_key(&context, &public_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let original = encryptor.encrypt(&Plaintext::create_constant(1)?)?;
+    // both variants chain on their previous result, only the in place one
+    // reuses a single destination: the noise is irrelevant to the timings
+    let micros = |nanos: u128, loops: u128| nanos as f64 / loops as f64 / 1_000.0;
+    debug!("|-------------------|-----------|----------|-----------|");
+    debug!("| operation         | allocated | in place | new c.t.  |");
+    debug!("|-------------------|-----------|----------|-----------|");
+
+    let loops = 1000u128;
+    let mut sum = original.clone()?;
+    let now = Instant::now();
+    for _ in 0..loops {
+        sum = evaluator.add(&sum, &original)?;
+    }
+    let add_nanos = now.elapsed().as_nanos();
+    let mut sum = original.clone()?;
+    let now = Instant::now();
+    for _ in 0..loops {
+        evaluator.add_assign(&mut sum, &original)?;
+    }
+    let add_assign_nanos = now.elapsed().as_nanos();
+    debug!(
+        "| add               | {:>6.1} µs | {:>5.1} µs | 1 vs 0    |",
+        micros(add_nanos, loops),
+        micros(add_assign_nanos, loops)
+    );
+
+    let loops = 100u128;
+    let mut product = original.clone()?;
+    let now = Instant::now();
+    for _ in 0..loops {
+        product = evaluator.mul(&product, &original)?;
+        product = evaluator.relinearize(&product, &relinearization_keys)?;
+    }
+    let mul_nanos = now.elapsed().as_nanos();
+    let mut product = original.clone()?;
+    let now = Instant::now();
+    for _ in 0..loops {
+        evaluator.mul_assign(&mut product, &original)?;
+        evaluator.relinearize_inplace(&mut product, &relinearization_keys)?;
+    }
+    let mul_assign_nanos = now.elapsed().as_nanos();
+    debug!(
+        "| mul + relinearize | {:>6.0} µs | {:>5.0} µs | 2 vs 0    |",
+        micros(mul_nanos, loops),
+        micros(mul_assign_nanos, loops)
+    );
+    debug!("|-------------------|-----------|----------|-----------|");
+    Ok(())
+}
+
+#[test]
+#[ignore]
+fn test_noise_budget() -> Result<()> {
+    // create a modulus for batching
+    let security_level = SecurityLevel::Tc128;
+    debug!("|--------|------------|------|------|---------|------|------|---------|------|");
+    debug!("| degree |    modulus | bits | m.p. | µs/slot |  ms  | mul. | µs/slot |  ms  |");
+    for bits_size in 22u8..30u8 {
+        debug!("|--------|------------|------|------|---------|------|------|---------|------|");
+        for degree_index in 2..4 {
+            let poly_modulus_degree = 4096usize * (1usize << degree_index);
+            print!("|{:>7} |", poly_modulus_degree);
+            let slots = poly_modulus_degree;
+            let plain_modulus = match SmallModulus::for_batching(poly_modulus_degree, bits_size) {
+                Ok(sm) => sm.value()?,
+                Err(_) => {
+                    debug!(" FAILURE creating the modulus-------|------|------|---------|------|");
+                    continue;
+                }
+            };
+            print!("{:>11} |{:>5} |", plain_modulus, bits_size);
+            let mut vector: Vec<u64> = Vec::with_capacity(slots);
+            for _col in 0..slots {
+                vector.push(thread_rng().gen::<u64>() % plain_modulus);
+            }
+
+            let params = Params::create(Scheme::Bfv)?;
+            params.set_poly_modulus_degree(poly_modulus_degree)?;
+            assert_eq!(poly_modulus_degree, params.get_poly_modulus_degree()?);
+            params.set_coeff_modulus(&params.bfv_default(security_level)?)?;
+            params.set_plain_modulus(plain_modulus)?;
+            let context = Context::create(params, security_level, true)?;
+
+            // Key Generation
+            let key_generator = KeyGenerator::create(&context)?;
+            let public_key = key_generator.public_key()?;
+            let secret_key = key_generator.secret_key()?;
+            let relinearization_keys = key_generator.relinearization_keys()?;
+
+            // Batch Encoding
+            let batch_encoder = BatchEncoder::create(&context)?;
+            assert_eq!(slots, batch_encoder.slot_count()?);
+            let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+            let decryptor = Decryptor::create(&context, &secret_key)?;
+
+            // check that the plain text decodes properly and create the sliding vector
+            let plain_text = batch_encoder.encode(&mut vector)?;
+            let v = batch_encoder.decode(&plain_text)?;
+            assert_eq!(vector, v);
+
+            let original_cipher_text = encryptor.encrypt(&plain_text)?;
+
+            // perform multiplications
+            let evaluator = Evaluator::create(&context)?;
+            let loops = 5usize;
+            let mut mul_plain_round = 1usize;
+            let mut mul_plain_nanos = 0u128;
+            let mut mul_round = 1usize;
+            let mut mul_nanos = 0u128;
+            for _l in 0..loops {
+                let mut mul_plain_cipher_text = original_cipher_text.clone()?;
+                let mut mul_cipher_text = original_cipher_text.clone()?;
+                loop {
+                    let mul_plain_now = Instant::now();
+                    mul_plain_cipher_text =
+                        evaluator.mul_plain(&mul_plain_cipher_text, &plain_text)?; //&original_cipher_text)?;
+                    mul_plain_cipher_text =
+                        evaluator.relinearize(&mul_plain_cipher_text, &relinearization_keys)?;
+                    let mul_plain_noise_budget =
+                        decryptor.invariant_noise_budget(&mul_plain_cipher_text)?;
+                    if mul_plain_noise_budget > 0 {
+                        mul_plain_nanos += mul_plain_now.elapsed().as_nanos();
+                        mul_plain_round += 1;
+                    }
+                    //
+                    let mul_now = Instant::now();
+                    mul_cipher_text = evaluator.mul(&mul_cipher_text, &original_cipher_text)?; //&original_cipher_text)?;
+                    mul_cipher_text =
+                        evaluator.relinearize(&mul_cipher_text, &relinearization_keys)?;
+                    let mul_noise_budget = decryptor.invariant_noise_budget(&mul_cipher_text)?;
+                    if mul_noise_budget > 0 {
+                        mul_nanos += mul_now.elapsed().as_nanos();
+                        mul_round += 1;
+                    }
+                    if mul_noise_budget <= 0 && mul_plain_noise_budget <= 0 {
+                        break;
+                    }
+                }
+            }
+            print!(
+                "  {:>2}  |{:>8} |{:>5} |",
+                mul_plain_round / loops,
+                mul_plain_nanos / (mul_plain_round as u128) / (slots as u128) / 1_000,
+                mul_plain_nanos / (mul_plain_round as u128) / 1_000_000,
+            );
+            debug!(
+                "  {:>2}  |{:>8} |{:>5} |",
+                mul_round / loops,
+                mul_nanos / (mul_round as u128) / (slots as u128) / 1_000,
+                mul_nanos / (mul_round as u128) / 1_000_000,
+            );
+        }
+    }
+    debug!("|--------|------------|------|------|---------|------|------|---------|------|");
+    Ok(())
+}
+
+#[test]
+fn test_memory_pool() -> Result<()> {
+    Plaintext::create_in_pool(MemoryPoolHandle::to_thread_local_pool()?)?;
+    Ok(())
+}
+
+#[test]
+fn test_set_params_bfv() -> Result<()> {
+    let security_level = SecurityLevel::Tc128;
     let poly_modulus_degree = 8192 * 2;
-    let params = Params::create(SCHEME_BFV)?;
+    let params = Params::create(Scheme::Bfv)?;
     params.set_poly_modulus_degree(poly_modulus_degree)?;
     assert_eq!(poly_modulus_degree, params.get_poly_modulus_degree()?);
     params.set_coeff_modulus(&params.bfv_default(security_level)?)?;
+    params.set_plain_modulus(1024)?;
+    let context = Context::create(params, security_level, true)?;
+    assert_eq!("success", context.get_error_name()?);
+    assert!(!context.get_error_msg()?.is_empty());
     Ok(())
 }
 
 #[test]
 fn test_set_params_ckks() -> Result<()> {
     let poly_modulus_degree = 8192 * 2;
-    let params = Params::create(SCHEME_CKKS)?;
+    let params = Params::create(Scheme::Ckks)?;
     params.set_poly_modulus_degree(8192 * 2)?;
     assert_eq!(poly_modulus_degree, params.get_poly_modulus_degree()?);
     let mut bits_sizes = vec![60, 30, 30, 30, 60]; // sum is 140
@@ -986,9 +2397,236 @@ fn test_set_params_ckks() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_params_scheme() -> Result<()> {
+    for scheme in [Scheme::Bfv, Scheme::Ckks, Scheme::Bgv] {
+        let params = Params::create(scheme)?;
+        params.set_poly_modulus_degree(4096)?;
+        assert_eq!(scheme, params.scheme()?);
+        // the scheme travels with the serialized params
+        let mut saved = params.save()?;
+        let loaded = Params::load(&mut saved)?;
+        assert_eq!(scheme, loaded.scheme()?);
+        assert_eq!(4096, loaded.get_poly_modulus_degree()?);
+    }
+    // the default coefficients modulus is not available for CKKS
+    let params = Params::create(Scheme::Ckks)?;
+    params.set_poly_modulus_degree(4096)?;
+    assert!(params.bfv_default(SecurityLevel::Tc128).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_security_level() -> Result<()> {
+    assert_eq!(SecurityLevel::Tc128, SecurityLevel::default());
+    for security_level in [
+        SecurityLevel::None,
+        SecurityLevel::Tc128,
+        SecurityLevel::Tc192,
+        SecurityLevel::Tc256,
+    ] {
+        assert_eq!(
+            security_level,
+            SecurityLevel::try_from(i32::from(security_level))?
+        );
+    }
+    assert!(SecurityLevel::try_from(100).is_err());
+    // a context reports the level it was created with
+    let params = Params::create(Scheme::Bfv)?;
+    params.set_poly_modulus_degree(4096)?;
+    params.set_coeff_modulus(&params.bfv_default(SecurityLevel::Tc192)?)?;
+    params.set_plain_modulus(1024)?;
+    let context = Context::create(params, SecurityLevel::Tc192, true)?;
+    assert_eq!(SecurityLevel::Tc192, context.security_level());
+    Ok(())
+}
+
+#[test]
+fn test_params_builder() -> Result<()> {
+    // BFV with the default coefficient modulus and batching
+    let context = ParamsBuilder::new(Scheme::Bfv)
+        .plain_modulus_batching(20)
+        .coeff_modulus_default()
+        .poly_modulus_degree(4096)
+        .build()?;
+    assert_eq!(Scheme::Bfv, context.parameters()?.scheme()?);
+    assert_eq!(4096, context.parameters()?.get_poly_modulus_degree()?);
+    assert_eq!(SecurityLevel::Tc128, context.security_level());
+    let batch_encoder = BatchEncoder::create(&context)?;
+    assert_eq!(4096, batch_encoder.slot_count()?);
+
+    // CKKS with bit sizes
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    assert_eq!(4, context.get_coeff_modulus()?.len());
+
+    // BGV with explicit primes taken from another context
+    let primes = context.get_coeff_modulus()?;
+    let context = ParamsBuilder::new(Scheme::Bgv)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_primes(&primes)
+        .plain_modulus(1024)
+        .security_level(SecurityLevel::Tc128)
+        .build()?;
+    assert_eq!(1024, context.parameters()?.get_plain_modulus()?);
+    Ok(())
+}
+
+#[test]
+fn test_params_builder_errors() -> Result<()> {
+    // all the problems are reported at once
+    let err = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(5000)
+        .coeff_modulus_default()
+        .plain_modulus(1024)
+        .build()
+        .err()
+        .expect("the parameters should be invalid")
+        .to_string();
+    assert!(err.contains("power of 2"), "{}", err);
+    assert!(err.contains("not available for CKKS"), "{}", err);
+    assert!(err.contains("cannot be set for CKKS"), "{}", err);
+
+    let err = ParamsBuilder::new(Scheme::Bfv)
+        .build()
+        .err()
+        .expect("the parameters should be invalid")
+        .to_string();
+    assert!(err.contains("degree is not set"), "{}", err);
+    assert!(err.contains("coefficient modulus is not set"), "{}", err);
+    assert!(err.contains("plain modulus is required"), "{}", err);
+
+    // errors detected by SEAL are reported with their name
+    let err = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(4096)
+        .coeff_modulus_bit_sizes(&[60, 60, 60])
+        .build()
+        .err()
+        .expect("the parameters should be insecure")
+        .to_string();
+    assert!(err.contains("invalid_parameters_insecure"), "{}", err);
+    Ok(())
+}
+
+#[test]
+fn test_params_planner_bfv() -> Result<()> {
+    let depth = 2;
+    let (params, report) = ParamsPlanner::bfv(depth, 20).plan()?;
+    assert_eq!(
+        report.poly_modulus_degree,
+        params.get_poly_modulus_degree()?
+    );
+    let total_bits: i32 = report.coeff_modulus_bit_sizes.iter().sum();
+    assert!(total_bits as u32 <= report.max_bit_count);
+    assert!(report.expected_noise_budget.unwrap_or_default() > 0);
+    let plain_modulus = report
+        .plain_modulus
+        .expect("BFV should have a plain modulus");
+    assert_eq!(plain_modulus, params.get_plain_modulus()?);
+    let context = Context::create(params, SecurityLevel::Tc128, true)?;
+
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let batch_encoder = BatchEncoder::create(&context)?;
+
+    // the planned depth of squarings must decrypt properly
+    let mut vector: Vec<u64> = (0..batch_encoder.slot_count()? as u64).collect();
+    let mut expected = vector.clone();
+    let mut cipher_text = encryptor.encrypt(&batch_encoder.encode(&mut vector)?)?;
+    for _ in 0..depth {
+        cipher_text = evaluator.square(&cipher_text)?;
+        cipher_text = evaluator.relinearize(&cipher_text, &relinearization_keys)?;
+        expected = expected
+            .iter()
+            .map(|&v| ((v as u128 * v as u128) % plain_modulus as u128) as u64)
+            .collect();
+    }
+    assert!(decryptor.invariant_noise_budget(&cipher_text)? > 0);
+    assert_eq!(
+        expected,
+        batch_encoder.decode(&decryptor.decrypt(&cipher_text)?)?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_params_planner_bgv() -> Result<()> {
+    let depth = 3;
+    let (params, report) = ParamsPlanner::bgv(depth, 20).plan()?;
+    assert_eq!(Scheme::Bgv, params.scheme()?);
+    // one prime to switch away after each multiplication
+    assert!(report.levels >= depth);
+    assert_eq!(report.levels + 2, report.coeff_modulus_bit_sizes.len());
+    let context = Context::create(params, SecurityLevel::Tc128, true)?;
+    assert_eq!(report.levels + 1, context.get_coeff_modulus()?.len());
+    Ok(())
+}
+
+#[test]
+fn test_params_planner_ckks() -> Result<()> {
+    let depth = 2;
+    let (params, report) = ParamsPlanner::ckks(depth, 40, 20).plan()?;
+    assert_eq!(vec![60, 40, 40, 60], report.coeff_modulus_bit_sizes);
+    assert_eq!(depth, report.levels);
+    assert_eq!(None, report.plain_modulus);
+    assert_eq!(None, report.expected_noise_budget);
+    let context = Context::create(params, SecurityLevel::Tc128, true)?;
+
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+
+    // the planned depth of squarings and rescales must decrypt properly
+    let mut input = [0.5, 1.1, 2.2, 3.3];
+    let scale = 2.0_f64.powi(40);
+    let mut cipher_text = encryptor.encrypt(&ckks_encoder.encode(&mut input, &scale)?)?;
+    for _ in 0..depth {
+        cipher_text = evaluator.square(&cipher_text)?;
+        cipher_text = evaluator.relinearize(&cipher_text, &relinearization_keys)?;
+        cipher_text = evaluator.rescale_to_next(&cipher_text)?;
+    }
+    let output = ckks_encoder.decode(&decryptor.decrypt(&cipher_text)?)?;
+    let epsilon = 0.001;
+    for (i, o) in input.iter().zip(output.iter()) {
+        assert!((i.powi(4) - *o).abs() < epsilon, "{} != {}", i.powi(4), o);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_params_planner_errors() -> Result<()> {
+    let err = ParamsPlanner::bfv(40, 20)
+        .plan()
+        .err()
+        .expect("the depth should be too large")
+        .to_string();
+    assert!(err.contains("no polynomial modulus degree"), "{}", err);
+    assert!(ParamsPlanner::bfv(1, 1).plan().is_err());
+    assert!(ParamsPlanner::ckks(1, 50, 20).plan().is_err());
+    // a higher security level needs a larger degree
+    let (_, tc128) = ParamsPlanner::ckks(4, 40, 20).plan()?;
+    let (_, tc256) = ParamsPlanner::ckks(4, 40, 20)
+        .security_level(SecurityLevel::Tc256)
+        .plan()?;
+    assert!(tc256.poly_modulus_degree > tc128.poly_modulus_degree);
+    Ok(())
+}
+
 #[test]
 fn test_mod_switch_to_next() -> Result<()> {
-    let security_level = 128u8;
+    let security_level = SecurityLevel::Tc128;
     debug!("|-------|-------||-------|----------||-------|----------||------|");
     debug!("| deg.  |  bits || noise | size(kb) || noise | size(kb) || gain |");
     debug!("|-------|-------||-------|----------||-------|----------||------|");
@@ -998,7 +2636,7 @@ fn test_mod_switch_to_next() -> Result<()> {
             let plain_modulus =
                 SmallModulus::for_batching(poly_modulus_degree, bits_size)?.value()?;
 
-            let params = Params::create(SCHEME_BFV)?;
+            let params = Params::create(Scheme::Bfv)?;
             params.set_poly_modulus_degree(poly_modulus_degree)?;
             params.set_coeff_modulus(&params.bfv_default(security_level)?)?;
             params.set_plain_modulus(plain_modulus)?;
@@ -1062,10 +2700,316 @@ fn test_mod_switch_to_next() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_context_data_chain() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    let primes = context.get_coeff_modulus()?;
+    assert_eq!(4, primes.len());
+
+    // the key level holds all the primes, each level drops the last one
+    let chain = context.chain()?;
+    assert_eq!(4, chain.len());
+    for (i, context_data) in chain.iter().enumerate() {
+        assert_eq!(3 - i, context_data.chain_index()?);
+        assert_eq!(primes[..4 - i].to_vec(), context_data.coeff_modulus()?);
+        // the bit count of a product of primes is at most the sum of their bit counts
+        let bit_count = context_data.total_coeff_modulus_bit_count()?;
+        let max_bit_count = [200, 140, 100, 60][i];
+        assert!(bit_count <= max_bit_count && bit_count + 4 > max_bit_count);
+        let qualifiers = context_data.qualifiers()?;
+        assert!(qualifiers.parameters_set);
+        assert!(qualifiers.using_ntt);
+    }
+    assert_eq!(SecurityLevel::Tc128, chain[0].qualifiers()?.security_level);
+
+    // navigation
+    let key = context.key_context_data()?;
+    let first = context.first_context_data()?;
+    let last = context.last_context_data()?;
+    assert!(key.prev()?.is_none());
+    assert!(last.next()?.is_none());
+    assert_eq!(context.first_parms_id()?, first.parms_id()?);
+    assert_eq!(
+        key.parms_id()?,
+        first.prev()?.expect("the key level").parms_id()?
+    );
+    assert_eq!(0, last.chain_index()?);
+    let last_parms_id = last.parms_id()?;
+    assert_eq!(
+        Some(0),
+        context
+            .context_data(&last_parms_id)?
+            .map(|data| data.chain_index())
+            .transpose()?
+    );
+    assert!(context.context_data_at(4).is_err());
+
+    // use a chain index as the target of a modulus switch
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+    let mut input = [1.5, 2.5];
+    let cipher_text = encryptor.encrypt(&ckks_encoder.encode(&mut input, &2.0_f64.powi(40))?)?;
+    assert_eq!(first.parms_id()?, cipher_text.parms_id()?);
+    let parms_id = context.parms_id_at(0)?;
+    let switched = evaluator.mod_switch_to(&cipher_text, &parms_id)?;
+    assert_eq!(parms_id, switched.parms_id()?);
+    assert_eq!(1, switched.get_coeff_modulus_length()?);
+    let output = ckks_encoder.decode(&decryptor.decrypt(&switched)?)?;
+    for (i, o) in input.iter().zip(output.iter()) {
+        assert!((*i - *o).abs() < 0.0001);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parms_id() -> Result<()> {
+    let parms_id = ParmsId::from([1, 0xab, u64::MAX, 0]);
+    assert_eq!(
+        "0000000000000001 00000000000000ab ffffffffffffffff 0000000000000000",
+        parms_id.to_string()
+    );
+    assert_eq!([1, 0xab, u64::MAX, 0], <[u64; 4]>::from(parms_id));
+    assert!(!parms_id.is_zero());
+    assert!(ParmsId::default().is_zero());
+
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    // all the levels have distinct ids
+    let ids = context
+        .chain()?
+        .iter()
+        .map(|context_data| context_data.parms_id())
+        .collect::<Result<std::collections::HashSet<ParmsId>, SealError>>()?;
+    assert_eq!(4, ids.len());
+    assert_eq!(context.last_parms_id()?, context.parms_id_at(0)?);
+    assert_eq!(context.key_parms_id()?, context.parameters()?.parms_id()?);
+
+    // keys are at the key level, data at the first level
+    let key_generator = KeyGenerator::create(&context)?;
+    let secret_key = key_generator.secret_key()?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    assert_eq!(context.key_parms_id()?, secret_key.parms_id()?);
+    assert_eq!(context.key_parms_id()?, relinearization_keys.parms_id()?);
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+    let plain_text = ckks_encoder.encode(&mut [1.0, 2.0], &2.0_f64.powi(40))?;
+    assert_eq!(context.first_parms_id()?, plain_text.parms_id()?);
+    // BFV plain texts are not bound to a level
+    assert!(Plaintext::create_constant(3)?.parms_id()?.is_zero());
+    Ok(())
+}
+
+#[test]
+fn test_level_mismatch() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+    let plain_text = ckks_encoder.encode(&mut [1.0, 2.0], &2.0_f64.powi(40))?;
+    let cipher_text = encryptor.encrypt(&plain_text)?;
+    let switched = evaluator.mod_switch_to_next(&cipher_text)?;
+
+    let err = evaluator
+        .add(&cipher_text, &switched)
+        .err()
+        .expect("the levels should not match");
+    assert!(matches!(err, SealError::LevelMismatch { .. }));
+    assert_eq!(err.code(), None);
+    let err = err.to_string();
+    assert!(err.contains("different levels"), "{}", err);
+    assert!(err.contains(&switched.parms_id()?.to_string()), "{}", err);
+    assert!(evaluator.mul(&switched, &cipher_text).is_err());
+    assert!(evaluator.mul_plain(&switched, &plain_text).is_err());
+
+    // once switched to the same level, the operation succeeds
+    let plain_text = evaluator.mod_switch_to_plain_text(&plain_text, &switched.parms_id()?)?;
+    evaluator.add_plain(&switched, &plain_text)?;
+    Ok(())
+}
+
+#[test]
+fn test_context_outlived() -> Result<()> {
+    // the context goes out of scope before the objects created from it
+    let (encryptor, decryptor, evaluator, batch_encoder) = {
+        let context = ParamsBuilder::new(Scheme::Bfv)
+            .poly_modulus_degree(4096)
+            .coeff_modulus_default()
+            .plain_modulus_batching(20)
+            .build()?;
+        let key_generator = KeyGenerator::create(&context)?;
+        let public_key = key_generator.public_key()?;
+        let secret_key = key_generator.secret_key()?;
+        // an empty cipher text, the context is not kept
+        let cipher_text = Ciphertext::create_with_context(&context)?;
+        assert_eq!(0, cipher_text.size()?);
+        (
+            Encryptor::create(&context, &public_key, &secret_key)?,
+            Decryptor::create(&context, &secret_key)?,
+            Evaluator::create(&context)?,
+            BatchEncoder::create(&context)?,
+        )
+    };
+    let mut vector = vec![3u64; batch_encoder.slot_count()?];
+    let cipher_text = encryptor.encrypt(&batch_encoder.encode(&mut vector)?)?;
+    let cipher_text = evaluator.add(&cipher_text, &cipher_text)?;
+    assert_eq!(
+        vec![6u64; vector.len()],
+        batch_encoder.decode(&decryptor.decrypt(&cipher_text)?)?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    fn assert_send<T: Send>() {}
+    assert_send_sync::<Context>();
+    assert_send_sync::<Ciphertext>();
+    assert_send_sync::<Plaintext>();
+    assert_send_sync::<SmallModulus>();
+    assert_send_sync::<PublicKey>();
+    assert_send_sync::<SecretKey>();
+    assert_send_sync::<RelinearizationKeys>();
+    assert_send_sync::<GaloisKeys>();
+    assert_send_sync::<KeyGenerator>();
+    assert_send_sync::<Encryptor>();
+    assert_send_sync::<Decryptor>();
+    assert_send_sync::<Evaluator>();
+    assert_send_sync::<BatchEncoder>();
+    assert_send_sync::<CKKSEncoder>();
+    assert_send::<Params>();
+}
+
+#[test]
+fn test_multithreaded_mul_rotate() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let relinearization_keys = key_generator.relinearization_keys()?;
+    let galois_keys = key_generator.galois_keys()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let decryptor = Decryptor::create(&context, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+
+    let slots = ckks_encoder.slot_count()?;
+    let mut input: Vec<f64> = (0..slots).map(|i| (i % 16) as f64 / 4.0).collect();
+    let scale = 2.0_f64.powi(40);
+    let cipher_text = encryptor.encrypt(&ckks_encoder.encode(&mut input, &scale)?)?;
+
+    // the evaluator, the keys and the cipher text are shared by all threads
+    let results = std::thread::scope(|scope| {
+        let handles = (1..=8)
+            .map(|shift| {
+                let (evaluator, cipher_text) = (&evaluator, &cipher_text);
+                let (relinearization_keys, galois_keys) = (&relinearization_keys, &galois_keys);
+                scope.spawn(move || -> Result<(usize, Ciphertext)> {
+                    let squared = evaluator.square(cipher_text)?;
+                    let squared = evaluator.relinearize(&squared, relinearization_keys)?;
+                    let product = evaluator.mul(cipher_text, cipher_text)?;
+                    let product = evaluator.relinearize(&product, relinearization_keys)?;
+                    assert_eq!(squared.parms_id()?, product.parms_id()?);
+                    let squared = evaluator.rescale_to_next(&squared)?;
+                    Ok((
+                        shift,
+                        evaluator.rotate(&squared, shift as i32, galois_keys)?,
+                    ))
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("the thread panicked"))
+            .collect::<Result<Vec<(usize, Ciphertext)>>>()
+    })?;
+
+    for (shift, rotated) in results {
+        let output = ckks_encoder.decode(&decryptor.decrypt(&rotated)?)?;
+        for (i, o) in output.iter().take(32).enumerate() {
+            let expected = input[(i + shift) % slots].powi(2);
+            assert!((expected - o).abs() < 0.001, "{} != {}", expected, o);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_concurrent_creation() -> Result<()> {
+    // contexts and encoders are created under an internal lock
+    std::thread::scope(|scope| {
+        let handles = (0..4)
+            .map(|_| {
+                scope.spawn(|| -> Result<usize, SealError> {
+                    let context = ParamsBuilder::new(Scheme::Bfv)
+                        .poly_modulus_degree(4096)
+                        .coeff_modulus_default()
+                        .plain_modulus_batching(20)
+                        .build()?;
+                    let _key_generator = KeyGenerator::create(&context)?;
+                    BatchEncoder::create(&context)?.slot_count()
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(4096, handle.join().expect("the thread panicked")?);
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn test_scale_out_of_bounds() -> Result<()> {
+    let context = ParamsBuilder::new(Scheme::Ckks)
+        .poly_modulus_degree(8192)
+        .coeff_modulus_bit_sizes(&[60, 40, 40, 60])
+        .build()?;
+    let key_generator = KeyGenerator::create(&context)?;
+    let public_key = key_generator.public_key()?;
+    let secret_key = key_generator.secret_key()?;
+    let encryptor = Encryptor::create(&context, &public_key, &secret_key)?;
+    let evaluator = Evaluator::create(&context)?;
+    let mut ckks_encoder = CKKSEncoder::create(&context)?;
+    let plain_text = ckks_encoder.encode(&mut [1.0, 2.0], &2.0_f64.powi(40))?;
+    let cipher_text = encryptor.encrypt(&plain_text)?;
+
+    // 2^80 fits in the 140 bits of the first level, 2^160 does not
+    let squared = evaluator.mul(&cipher_text, &cipher_text)?;
+    let err = evaluator
+        .mul(&squared, &squared)
+        .err()
+        .expect("the scale should not fit");
+    assert!(
+        matches!(err, SealError::ScaleOutOfBounds { .. }),
+        "{:?}",
+        err
+    );
+    assert_eq!(err.code(), Some(0x80070057));
+    Ok(())
+}
+
 #[test]
 fn test_try_add_assign() -> Result<()> {
-    let params = Params::create(SCHEME_BFV)?;
-    let security_level = 128u8;
+    let params = Params::create(Scheme::Bfv)?;
+    let security_level = SecurityLevel::Tc128;
     let poly_modulus_degree = 4096;
     params.set_poly_modulus_degree(poly_modulus_degree)?;
     params.set_coeff_modulus(&params.bfv_default(security_level)?)?;
@@ -1091,7 +3035,7 @@ fn test_try_add_assign() -> Result<()> {
     // create a constant plain text in the thread local memory pool
     let value_a = 6u64;
     let plain_text_a = Plaintext::create_constant(value_a)?;
-    let cipher_text_a = encryptor.encrypt(&plain_text_a)?;
+    let mut cipher_text_a = encryptor.encrypt(&plain_text_a)?;
     let value_b = 7u64;
     let plain_text_b = Plaintext::create_constant(value_b)?;
     let cipher_text_b = encryptor.encrypt(&plain_text_b)?;